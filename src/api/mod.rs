@@ -0,0 +1,800 @@
+//! Queries against the [MediaWiki Action API] for data that can't be scraped from a single page body
+//!
+//! [MediaWiki Action API]: https://www.mediawiki.org/wiki/API:Main_page
+
+use std::collections::{HashMap, HashSet};
+use std::thread;
+
+use reqwest::Url;
+use serde_json::Value;
+use thiserror::Error;
+
+use super::fetch::{BodySource, DEFAULT_FETCHER, FetchConfig, SourceError};
+use super::{DEFAULT_LANG, Page, PageError, ReqwestError, WikipediaUrl};
+
+const WIKIDATA_API_URL: &str = "https://www.wikidata.org/w/api.php";
+
+/// The most titles the API accepts in a single `titles=` parameter
+const MAX_TITLES_PER_QUERY: usize = 50;
+
+/// This error covers all failures related to querying the MediaWiki API
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error("request to the MediaWiki API failed: '{0}'")]
+    RequestError(#[from] ReqwestError),
+    #[error("failed to load the page: '{0}'")]
+    PageError(#[from] PageError),
+    #[error("failed to parse the MediaWiki API response: '{0}'")]
+    JsonError(#[from] serde_json::Error),
+    #[error("unexpected MediaWiki API response: {0}")]
+    ResponseError(String),
+    #[error("'{0}' does not point to a Wikipedia article")]
+    NoTitleError(String),
+}
+
+impl From<SourceError> for ApiError {
+    /// Request failures are kept as [ApiError::RequestError]
+    fn from(error: SourceError) -> Self {
+        match PageError::from(error) {
+            PageError::RequestError(error) => ApiError::RequestError(error),
+            error => ApiError::PageError(error),
+        }
+    }
+}
+
+/// What API requests are sent through: the [BodySource] responses are loaded from, and the [FetchConfig] whose
+/// `maxlag` and retry settings apply
+///
+/// The API methods of a [Page] send their requests through the page's own source, this is for the ones that aren't
+/// made for a single page, like [Page::exists_many_with]
+#[derive(Debug, Clone, Copy)]
+pub struct ApiClient<'a> {
+    source: &'a dyn BodySource,
+    config: &'a FetchConfig,
+}
+
+impl<'a> ApiClient<'a> {
+    /// Create a new [ApiClient] loading responses from `source`, with the `maxlag` and retry settings of `config`
+    pub fn new(source: &'a dyn BodySource, config: &'a FetchConfig) -> Self {
+        ApiClient { source, config }
+    }
+
+    /// Send requests made for `page` through its [BodySource] and [Fetcher](crate::fetch::Fetcher)
+    pub fn of(page: &'a Page) -> Self {
+        ApiClient {
+            source: page.source(),
+            config: page.fetcher().get_config(),
+        }
+    }
+}
+
+impl Default for ApiClient<'static> {
+    /// Send requests through the default [Fetcher](crate::fetch::Fetcher)
+    fn default() -> Self {
+        ApiClient {
+            source: &*DEFAULT_FETCHER,
+            config: DEFAULT_FETCHER.get_config(),
+        }
+    }
+}
+
+/// Send a request to a MediaWiki API endpoint and parse the JSON response
+///
+/// The `maxlag` parameter of the client's config is sent with the request, and requests rejected because of
+/// replication lag are retried with the same backoff as other transient failures
+fn get_json<'a, I>(client: &ApiClient, endpoint: &str, params: I) -> Result<Value, ApiError>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let maxlag = client.config.get_maxlag().map(|maxlag| maxlag.to_string());
+
+    let mut url = Url::parse(endpoint).expect("API url is valid");
+
+    url.query_pairs_mut()
+        .extend_pairs([("format", "json"), ("formatversion", "2")])
+        .extend_pairs(params)
+        .extend_pairs(maxlag.iter().map(|maxlag| ("maxlag", maxlag.as_str())));
+
+    let mut attempt = 0;
+
+    loop {
+        let response: Value = serde_json::from_str(&client.source.fetch(&url)?)?;
+
+        let lagged = response.pointer("/error/code").and_then(Value::as_str) == Some("maxlag");
+
+        if lagged && attempt < client.config.get_max_retries() {
+            thread::sleep(client.config.get_retry_backoff() * 2u32.saturating_pow(attempt));
+            attempt += 1;
+            continue;
+        }
+
+        if let Some(error) = response.get("error") {
+            return Err(ApiError::ResponseError(error.to_string()));
+        }
+
+        return Ok(response);
+    }
+}
+
+/// Get the API endpoint of the Wikipedia of the language `lang`
+fn api_url(lang: &str) -> String {
+    format!("https://{lang}.wikipedia.org/w/api.php")
+}
+
+/// Send a query to the API of the Wikipedia of the language `lang`, following `continue` tokens until every batch
+/// of results has been fetched
+fn query_all(client: &ApiClient, lang: &str, params: &[(&str, &str)]) -> Result<Vec<Value>, ApiError> {
+    let mut responses = Vec::new();
+    let mut continue_params: Vec<(String, String)> = Vec::new();
+
+    loop {
+        let response = get_json(
+            client,
+            &api_url(lang),
+            [("action", "query")]
+                .into_iter()
+                .chain(params.iter().copied())
+                .chain(continue_params.iter().map(|(key, value)| (key.as_str(), value.as_str()))),
+        )?;
+
+        continue_params = match response.get("continue").and_then(Value::as_object) {
+            Some(continue_object) => continue_object
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+
+                    (key.clone(), value)
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        responses.push(response);
+
+        if continue_params.is_empty() {
+            return Ok(responses);
+        }
+    }
+}
+
+/// Get the title the API knows a page by, preferring the loaded title and falling back to the url path
+pub(crate) fn api_title(page: &Page) -> Result<String, ApiError> {
+    if let Some(title) = &page.title {
+        return Ok(title.clone());
+    }
+
+    if !page.get_url().path().starts_with("/wiki/") {
+        return Err(ApiError::NoTitleError(page.get_url().to_string()));
+    }
+
+    Some(page.url.title_from_url())
+        .filter(|title| !title.is_empty())
+        .ok_or_else(|| ApiError::NoTitleError(page.get_url().to_string()))
+}
+
+/// Create a titled [Page] from a title returned by the API
+///
+/// The page points to English Wikipedia, callers move it onto the right language with [Page::inherit_wiki]
+fn page_from_title(title: &str) -> Option<Page> {
+    let url = WikipediaUrl::from_title(title).ok()?;
+
+    Some(Page::new_with_title(url, title.to_string()))
+}
+
+/// Get the `from -> to` title mappings the API applied to the input titles, from the `normalized` list
+///
+/// The API normalizes titles like `waffle` or `Belgian_waffle` into `Waffle` and `Belgian waffle` and reports
+/// results under the normalized title, so this is needed to match results back to the titles that were sent
+fn normalizations(responses: &[Value]) -> HashMap<String, String> {
+    title_mappings(responses, "normalized")
+}
+
+/// Get the `from -> to` title mappings of the redirects the API followed, from the `redirects` list of queries
+/// sent with `redirects=1`
+///
+/// The mappings start from the normalized titles, see [normalizations]
+fn redirects(responses: &[Value]) -> HashMap<String, String> {
+    title_mappings(responses, "redirects")
+}
+
+/// Get the `from -> to` title mappings out of the list `list` of a batch of `action=query` responses
+fn title_mappings(responses: &[Value], list: &str) -> HashMap<String, String> {
+    let pointer = format!("/query/{list}");
+
+    responses
+        .iter()
+        .filter_map(|response| response.pointer(&pointer)?.as_array())
+        .flatten()
+        .filter_map(|normalization| {
+            Some((
+                normalization.get("from")?.as_str()?.to_string(),
+                normalization.get("to")?.as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Collect the backlinking [Page]s of each of `titles` out of a batch of `prop=linkshere` responses
+///
+/// A page's backlinks may be split across several responses when the results are continued
+fn backlinks_from_responses(responses: &[Value], titles: &[String]) -> Result<Vec<Vec<Page>>, ApiError> {
+    let normalized = normalizations(responses);
+
+    let mut backlinks: HashMap<&str, Vec<Page>> = HashMap::new();
+
+    for response in responses {
+        let pages = response
+            .pointer("/query/pages")
+            .and_then(Value::as_array)
+            .ok_or_else(|| ApiError::ResponseError("missing 'query.pages'".to_string()))?;
+
+        for page in pages {
+            let Some(title) = page.get("title").and_then(Value::as_str) else {
+                continue;
+            };
+
+            let links = page
+                .get("linkshere")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|link| link.get("title")?.as_str())
+                .filter_map(page_from_title);
+
+            backlinks.entry(title).or_default().extend(links);
+        }
+    }
+
+    Ok(titles
+        .iter()
+        .map(|title| {
+            let title = normalized.get(title).unwrap_or(title);
+
+            backlinks.get(title.as_str()).cloned().unwrap_or_default()
+        })
+        .collect())
+}
+
+/// Get the backlinking [Page]s of each of `titles` on the Wikipedia of the language `lang` in as few requests as
+/// possible
+pub(crate) fn backlinks_of(client: &ApiClient, lang: &str, titles: &[String]) -> Result<Vec<Vec<Page>>, ApiError> {
+    let mut backlinks = Vec::with_capacity(titles.len());
+
+    for chunk in titles.chunks(MAX_TITLES_PER_QUERY) {
+        let responses = query_all(client, lang, &[
+            ("prop", "linkshere"),
+            ("titles", &chunk.join("|")),
+            ("lhnamespace", "0"),
+            ("lhlimit", "max"),
+        ])?;
+
+        backlinks.extend(backlinks_from_responses(&responses, chunk)?);
+    }
+
+    Ok(backlinks)
+}
+
+/// Collect the [Page]s a page links to out of a batch of `prop=links` responses for a single title
+fn links_from_responses(responses: &[Value]) -> Result<Vec<Page>, ApiError> {
+    let mut links = Vec::new();
+
+    for response in responses {
+        let pages = response
+            .pointer("/query/pages")
+            .and_then(Value::as_array)
+            .ok_or_else(|| ApiError::ResponseError("missing 'query.pages'".to_string()))?;
+
+        links.extend(
+            pages
+                .iter()
+                .filter_map(|page| page.get("links")?.as_array())
+                .flatten()
+                .filter_map(|link| link.get("title")?.as_str())
+                .filter_map(page_from_title),
+        );
+    }
+
+    Ok(links)
+}
+
+/// Get the [Page]s of the articles that `title` links to on the Wikipedia of the language `lang`
+fn links_of(client: &ApiClient, lang: &str, title: &str) -> Result<Vec<Page>, ApiError> {
+    let responses = query_all(client, lang, &[
+        ("prop", "links"),
+        ("titles", title),
+        ("plnamespace", "0"),
+        ("pllimit", "max"),
+        ("redirects", "1"),
+    ])?;
+
+    links_from_responses(&responses)
+}
+
+/// Check whether each of `titles` exists out of a batch of `action=query` responses
+///
+/// Missing and invalid titles are flagged by the API with `missing` and `invalid` respectively
+fn existence_from_responses(responses: &[Value], titles: &[&str]) -> Result<HashMap<String, bool>, ApiError> {
+    let normalized = normalizations(responses);
+
+    let mut existing: HashMap<&str, bool> = HashMap::new();
+
+    for response in responses {
+        let pages = response
+            .pointer("/query/pages")
+            .and_then(Value::as_array)
+            .ok_or_else(|| ApiError::ResponseError("missing 'query.pages'".to_string()))?;
+
+        for page in pages {
+            let Some(title) = page.get("title").and_then(Value::as_str) else {
+                continue;
+            };
+
+            let flagged = |flag: &str| page.get(flag).and_then(Value::as_bool).unwrap_or(false);
+
+            existing.insert(title, !flagged("missing") && !flagged("invalid"));
+        }
+    }
+
+    Ok(titles
+        .iter()
+        .map(|title| {
+            let normalized_title = normalized.get(*title).map(String::as_str).unwrap_or(title);
+
+            (title.to_string(), existing.get(normalized_title).copied().unwrap_or(false))
+        })
+        .collect())
+}
+
+/// Get the title of the existing page each of `titles` resolves to, after normalization and redirects, out of a
+/// batch of `action=query&redirects=1` responses
+///
+/// Titles of pages that don't exist are left out
+fn resolved_titles_from_responses(responses: &[Value], titles: &[&str]) -> Result<HashMap<String, String>, ApiError> {
+    let normalized = normalizations(responses);
+    let redirected = redirects(responses);
+
+    let resolved = titles
+        .iter()
+        .map(|title| {
+            let normalized_title = normalized.get(*title).map(String::as_str).unwrap_or(title);
+            let target = redirected.get(normalized_title).map(String::as_str).unwrap_or(normalized_title);
+
+            (*title, target)
+        })
+        .collect::<Vec<_>>();
+
+    let targets = resolved.iter().map(|(_, target)| *target).collect::<Vec<_>>();
+    let existing = existence_from_responses(responses, &targets)?;
+
+    Ok(resolved
+        .into_iter()
+        .filter(|(_, target)| existing.get(*target).copied().unwrap_or(false))
+        .map(|(title, target)| (title.to_string(), target.to_string()))
+        .collect())
+}
+
+/// Get the Wikidata item id out of a `prop=pageprops` response
+fn wikidata_id_from_response(response: &Value) -> Option<String> {
+    response
+        .pointer("/query/pages/0/pageprops/wikibase_item")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Get the id of the primary `P31` (instance of) value out of a `wbgetentities` response, preferring claims
+/// ranked as preferred and skipping deprecated ones
+fn primary_type_from_response(response: &Value, id: &str) -> Option<String> {
+    let claims = response.get("entities")?.get(id)?.pointer("/claims/P31")?.as_array()?;
+
+    let has_rank = |claim: &Value, rank: &str| claim.get("rank").and_then(Value::as_str).unwrap_or("normal") == rank;
+
+    claims
+        .iter()
+        .find(|claim| has_rank(claim, "preferred"))
+        .or_else(|| claims.iter().find(|claim| has_rank(claim, "normal")))
+        .and_then(|claim| claim.pointer("/mainsnak/datavalue/value/id")?.as_str())
+        .map(str::to_string)
+}
+
+/// Get the English label of an entity out of a `wbgetentities` response
+fn label_from_response(response: &Value, id: &str) -> Option<String> {
+    response
+        .get("entities")?
+        .get(id)?
+        .pointer("/labels/en/value")?
+        .as_str()
+        .map(str::to_string)
+}
+
+impl Page {
+    /// Get the id of the [Wikidata] item the page is linked to, like `Q14364` for `Waffle`
+    ///
+    /// Returns `None` if the page doesn't exist or isn't linked to an item
+    ///
+    /// [Wikidata]: https://www.wikidata.org
+    pub fn get_wikidata_id(&self) -> Result<Option<String>, ApiError> {
+        let title = api_title(self)?;
+
+        let response = get_json(&ApiClient::of(self), &api_url(self.url.lang()), [
+            ("action", "query"),
+            ("prop", "pageprops"),
+            ("ppprop", "wikibase_item"),
+            ("redirects", "1"),
+            ("titles", &title),
+        ])?;
+
+        Ok(wikidata_id_from_response(&response))
+    }
+
+    /// Get the English label of the primary type of the page's [Wikidata] item, from its `P31` (instance of) claim
+    ///
+    /// For example `human` for a biography, `city` for a city or `food` for `Waffle`. Returns `None` if the page
+    /// isn't linked to an item or the item has no type
+    ///
+    /// [Wikidata]: https://www.wikidata.org
+    pub fn entity_type(&self) -> Result<Option<String>, ApiError> {
+        let Some(id) = self.get_wikidata_id()? else {
+            return Ok(None);
+        };
+
+        let response = get_json(&ApiClient::of(self), WIKIDATA_API_URL, [
+            ("action", "wbgetentities"),
+            ("ids", &id),
+            ("props", "claims"),
+        ])?;
+
+        let Some(type_id) = primary_type_from_response(&response, &id) else {
+            return Ok(None);
+        };
+
+        let response = get_json(&ApiClient::of(self), WIKIDATA_API_URL, [
+            ("action", "wbgetentities"),
+            ("ids", &type_id),
+            ("props", "labels"),
+            ("languages", "en"),
+        ])?;
+
+        Ok(label_from_response(&response, &type_id))
+    }
+
+    /// Check which of `titles` are existing English Wikipedia pages, using the MediaWiki API through the default
+    /// [Fetcher](crate::fetch::Fetcher), see [Page::exists_many_with]
+    pub fn exists_many(titles: &[&str]) -> Result<HashMap<String, bool>, ApiError> {
+        Self::exists_many_with(&ApiClient::default(), DEFAULT_LANG, titles)
+    }
+
+    /// Check which of `titles` are existing pages on the Wikipedia of the language `lang`, like `en` or `de`, using
+    /// the MediaWiki API through `client`
+    ///
+    /// The titles are checked in batches of 50 per request, and the result is keyed by the titles as given. Redirects
+    /// count as existing pages
+    pub fn exists_many_with(client: &ApiClient, lang: &str, titles: &[&str]) -> Result<HashMap<String, bool>, ApiError> {
+        let mut existing = HashMap::with_capacity(titles.len());
+
+        for chunk in titles.chunks(MAX_TITLES_PER_QUERY) {
+            let responses = query_all(client, lang, &[("titles", &chunk.join("|"))])?;
+
+            existing.extend(existence_from_responses(&responses, chunk)?);
+        }
+
+        Ok(existing)
+    }
+
+    /// Load the titles of all of `pages` that don't have one yet using the MediaWiki API, in batches of 50 per
+    /// request instead of fetching the body of each page
+    ///
+    /// Titles are resolved like [Page::get_title] would: pages that redirect get the title of the article they lead
+    /// to. Pages that don't exist are left without a title. Each batch is sent through the source (see
+    /// [Page::with_source]) of its first page
+    pub fn load_titles_batch(pages: &mut [Page]) -> Result<(), ApiError> {
+        let mut untitled: Vec<(usize, String, String)> = pages
+            .iter()
+            .enumerate()
+            .filter(|(_, page)| page.title.is_none())
+            .filter_map(|(position, page)| Some((position, page.url.lang().to_string(), api_title(page).ok()?)))
+            .collect();
+
+        // Batches can only hold titles from the same language of Wikipedia
+        untitled.sort_by(|(_, a, _), (_, b, _)| a.cmp(b));
+
+        for batch in untitled.chunk_by(|(_, a, _), (_, b, _)| a == b) {
+            for chunk in batch.chunks(MAX_TITLES_PER_QUERY) {
+                let (first, lang, _) = &chunk[0];
+                let titles = chunk.iter().map(|(_, _, title)| title.as_str()).collect::<Vec<_>>();
+
+                let responses = query_all(&ApiClient::of(&pages[*first]), lang, &[
+                    ("titles", &titles.join("|")),
+                    ("redirects", "1"),
+                ])?;
+
+                let resolved = resolved_titles_from_responses(&responses, &titles)?;
+
+                for (position, _, title) in chunk {
+                    pages[*position].title = resolved.get(title).cloned();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the union of the [Page]s found by scraping the body (see [Page::get_connections]) and the links the
+    /// MediaWiki API reports for the page, loading the body as well if necessary
+    ///
+    /// The API misses some links that come from transcluded templates while the body includes navigation links, so
+    /// this gives the most complete set of neighbours. Pages are deduplicated by their normalized url (see
+    /// [WikipediaUrl::normalized]), keeping the scraped pages first followed by the ones only the API found
+    pub fn get_connections_merged(&mut self) -> Result<Vec<Page>, ApiError> {
+        let mut connections = self.get_connections()?;
+        connections.extend(self.get_connections_api()?);
+
+        let mut seen = HashSet::new();
+
+        Ok(connections
+            .into_iter()
+            .filter(|page| seen.insert(page.url.normalized().get_url().clone()))
+            .collect())
+    }
+
+    /// Get a list of [Page]s for the Wikipedia articles this page links to, using the MediaWiki API instead of
+    /// scraping the body
+    ///
+    /// Only links to articles in the body of the page itself are listed, in alphabetical order, so this leaves out
+    /// the navigation and footer links that [Page::get_connections] picks up and doesn't break when the markup of
+    /// Wikipedia changes. Links through redirects are listed under the redirect's title
+    pub fn get_connections_api(&self) -> Result<Vec<Page>, ApiError> {
+        let links = links_of(&ApiClient::of(self), self.url.lang(), &api_title(self)?)?;
+
+        Ok(self.inherit_wiki(links))
+    }
+
+    /// Get a list of [Page]s for all of the Wikipedia articles that link to this page, using the MediaWiki API
+    pub fn get_backlinks(&self) -> Result<Vec<Page>, ApiError> {
+        let mut backlinks = backlinks_of(&ApiClient::of(self), self.url.lang(), &[api_title(self)?])?;
+
+        Ok(self.inherit_wiki(backlinks.remove(0)))
+    }
+
+    /// Get a list of [Page]s for all of the Wikipedia articles that link to both `a` and `b`, using the MediaWiki API
+    ///
+    /// The order follows the backlinks of `a`
+    pub fn common_backlinks(a: &Page, b: &Page) -> Result<Vec<Page>, ApiError> {
+        let mut backlinks = backlinks_of(&ApiClient::of(a), a.url.lang(), &[api_title(a)?, api_title(b)?])?;
+
+        let b_backlinks = backlinks
+            .remove(1)
+            .into_iter()
+            .map(|page| page.get_url().clone())
+            .collect::<HashSet<_>>();
+
+        Ok(a.inherit_wiki(
+            backlinks
+                .remove(0)
+                .into_iter()
+                .filter(|page| b_backlinks.contains(page.get_url()))
+                .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        ApiClient, api_title, backlinks_from_responses, existence_from_responses, label_from_response,
+        links_from_responses, page_from_title, primary_type_from_response, wikidata_id_from_response,
+    };
+    use crate::fetch::FetchConfig;
+    use crate::tests::MockSource;
+    use crate::{Page, WikipediaUrl};
+
+    #[test]
+    fn test_get_connections_api() {
+        let response = json!({
+            "batchcomplete": true,
+            "query": { "pages": [{ "pageid": 1, "ns": 0, "title": "Waffle", "links": [
+                { "ns": 0, "title": "Batter (cooking)" },
+                { "ns": 0, "title": "Belgium" }
+            ] }] }
+        });
+
+        let source = MockSource::new(&response.to_string());
+        let url = WikipediaUrl::from_path_with_lang("/wiki/Waffel", "de").unwrap();
+
+        let links = Page::with_source(url, source.clone()).get_connections_api().unwrap();
+
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].get_url().as_str(), "https://de.wikipedia.org/wiki/Batter_(cooking)");
+        assert_eq!(links[1].title.as_deref(), Some("Belgium"));
+
+        let fetched = source.fetched();
+        let query = fetched[0].query_pairs().collect::<Vec<_>>();
+
+        assert_eq!(fetched.len(), 1);
+        assert_eq!(fetched[0].host_str(), Some("de.wikipedia.org"));
+        assert!(query.contains(&("prop".into(), "links".into())));
+        assert!(query.contains(&("titles".into(), "Waffel".into())));
+    }
+
+    #[test]
+    fn test_load_titles_batch() {
+        let response = json!({
+            "batchcomplete": true,
+            "query": {
+                "normalized": [{ "fromencoded": false, "from": "waffle", "to": "Waffle" }],
+                "redirects": [{ "from": "USA", "to": "United States" }],
+                "pages": [
+                    { "pageid": 1, "ns": 0, "title": "Waffle" },
+                    { "pageid": 2, "ns": 0, "title": "United States" },
+                    { "pageid": 3, "ns": 0, "title": "Belgium" }
+                ]
+            }
+        });
+
+        let source = MockSource::new(&response.to_string());
+
+        let mut pages = ["/wiki/waffle", "/wiki/USA", "/wiki/Belgium"]
+            .map(|path| Page::with_source(WikipediaUrl::from_path(path).unwrap(), source.clone()));
+
+        Page::load_titles_batch(&mut pages).unwrap();
+
+        let titles = pages.iter().map(|page| page.title.as_deref()).collect::<Vec<_>>();
+
+        assert_eq!(titles, vec![Some("Waffle"), Some("United States"), Some("Belgium")]);
+
+        let fetched = source.fetched();
+        let query = fetched[0].query_pairs().collect::<Vec<_>>();
+
+        assert_eq!(fetched.len(), 1);
+        assert!(query.contains(&("titles".into(), "waffle|USA|Belgium".into())));
+        assert!(query.contains(&("redirects".into(), "1".into())));
+    }
+
+    #[test]
+    fn test_exists_many_with() {
+        let response = json!({
+            "batchcomplete": true,
+            "query": { "pages": [
+                { "pageid": 1, "ns": 0, "title": "Waffel" },
+                { "ns": 0, "title": "Waffeleisen des Untergangs", "missing": true }
+            ] }
+        });
+
+        let source = MockSource::new(&response.to_string());
+        let config = FetchConfig::default();
+
+        let existing =
+            Page::exists_many_with(&ApiClient::new(&*source, &config), "de", &["Waffel", "Waffeleisen des Untergangs"]).unwrap();
+
+        assert!(existing["Waffel"]);
+        assert!(!existing["Waffeleisen des Untergangs"]);
+
+        let fetched = source.fetched();
+
+        assert_eq!(fetched.len(), 1);
+        assert_eq!(fetched[0].host_str(), Some("de.wikipedia.org"));
+    }
+
+    #[test]
+    fn test_page_from_title_escapes_path() {
+        let page = page_from_title("Why?").unwrap();
+
+        assert_eq!(page.get_url().as_str(), "https://en.wikipedia.org/wiki/Why%3F");
+        assert_eq!(page.get_url().query(), None);
+        assert_eq!(page.url.title_from_url(), "Why?");
+        assert_eq!(page_from_title("C#").unwrap().get_url().fragment(), None);
+    }
+
+    #[test]
+    fn test_api_title_from_url() {
+        let url = WikipediaUrl::from_path("/wiki/Cura%C3%A7ao_Island").unwrap();
+
+        assert_eq!(api_title(&Page::new(url)).unwrap().as_str(), "Curaçao Island")
+    }
+
+    #[test]
+    fn test_backlinks_from_responses() {
+        let responses = [
+            json!({
+                "continue": { "lhcontinue": "123", "continue": "||" },
+                "query": {
+                    "normalized": [
+                        { "fromencoded": false, "from": "waffle", "to": "Waffle" },
+                        { "fromencoded": false, "from": "Belgian_waffle", "to": "Belgian waffle" }
+                    ],
+                    "pages": [
+                        { "pageid": 1, "ns": 0, "title": "Waffle", "linkshere": [{ "pageid": 3, "ns": 0, "title": "Breakfast" }] },
+                        { "pageid": 2, "ns": 0, "title": "Belgian waffle" }
+                    ]
+                }
+            }),
+            json!({
+                "query": {
+                    "normalized": [{ "fromencoded": false, "from": "waffle", "to": "Waffle" }],
+                    "pages": [
+                        { "pageid": 1, "ns": 0, "title": "Waffle", "linkshere": [{ "pageid": 4, "ns": 0, "title": "Liège" }] },
+                        { "pageid": 2, "ns": 0, "title": "Belgian waffle", "linkshere": [{ "pageid": 3, "ns": 0, "title": "Breakfast" }] }
+                    ]
+                }
+            }),
+        ];
+
+        let backlinks =
+            backlinks_from_responses(&responses, &["waffle".to_string(), "Belgian_waffle".to_string()]).unwrap();
+
+        assert_eq!(backlinks[0].len(), 2);
+        assert_eq!(backlinks[0][0].title.as_deref(), Some("Breakfast"));
+        assert_eq!(backlinks[0][1].get_url().path(), "/wiki/Li%C3%A8ge");
+        assert_eq!(backlinks[1].len(), 1);
+        assert_eq!(backlinks[1][0].title.as_deref(), Some("Breakfast"));
+    }
+
+    #[test]
+    fn test_links_from_responses() {
+        let responses = [
+            json!({
+                "continue": { "plcontinue": "1|0|Breakfast", "continue": "||" },
+                "query": { "pages": [{ "pageid": 1, "ns": 0, "title": "Waffle", "links": [
+                    { "ns": 0, "title": "Batter (cooking)" },
+                    { "ns": 0, "title": "Belgium" }
+                ] }] }
+            }),
+            json!({
+                "query": { "pages": [{ "pageid": 1, "ns": 0, "title": "Waffle", "links": [{ "ns": 0, "title": "Breakfast" }] }] }
+            }),
+        ];
+
+        let links = links_from_responses(&responses).unwrap();
+
+        assert_eq!(links.len(), 3);
+        assert_eq!(links[0].get_url().path(), "/wiki/Batter_(cooking)");
+        assert_eq!(links[2].title.as_deref(), Some("Breakfast"));
+    }
+
+    #[test]
+    fn test_existence_from_responses() {
+        let responses = [json!({
+            "query": {
+                "normalized": [{ "fromencoded": false, "from": "waffle", "to": "Waffle" }],
+                "pages": [
+                    { "pageid": 1, "ns": 0, "title": "Waffle" },
+                    { "ns": 0, "title": "Waffle iron of doom", "missing": true },
+                    { "title": "<Waffle>", "invalidreason": "The requested page title contains invalid characters", "invalid": true }
+                ]
+            }
+        })];
+
+        let existing = existence_from_responses(&responses, &["waffle", "Waffle iron of doom", "<Waffle>"]).unwrap();
+
+        assert_eq!(existing.len(), 3);
+        assert!(existing["waffle"]);
+        assert!(!existing["Waffle iron of doom"]);
+        assert!(!existing["<Waffle>"]);
+    }
+
+    #[test]
+    fn test_entity_type_responses() {
+        let pageprops = json!({
+            "query": { "pages": [{ "pageid": 1, "ns": 0, "title": "Waffle", "pageprops": { "wikibase_item": "Q14364" } }] }
+        });
+
+        let claims = json!({
+            "entities": { "Q14364": { "claims": { "P31": [
+                { "mainsnak": { "datavalue": { "value": { "id": "Q746549" } } }, "rank": "normal" },
+                { "mainsnak": { "datavalue": { "value": { "id": "Q2095" } } }, "rank": "preferred" }
+            ] } } }
+        });
+
+        let labels = json!({
+            "entities": { "Q2095": { "labels": { "en": { "language": "en", "value": "food" } } } }
+        });
+
+        assert_eq!(wikidata_id_from_response(&pageprops).as_deref(), Some("Q14364"));
+        assert_eq!(primary_type_from_response(&claims, "Q14364").as_deref(), Some("Q2095"));
+        assert_eq!(label_from_response(&labels, "Q2095").as_deref(), Some("food"));
+    }
+}
@@ -0,0 +1,766 @@
+//! Configuration for how pages are fetched from Wikipedia
+//!
+//! ```rust
+//! # use std::time::Duration;
+//! # use wikipedia_network::fetch::{FetchConfig, FetchProfile};
+//! # fn main() -> Result<(), reqwest::Error> {
+//! // Start from the polite preset and override a single field
+//! let fetcher = FetchConfig::from_profile(FetchProfile::Polite)
+//!     .timeout(Duration::from_secs(10))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, LazyLock, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reqwest::blocking::{Client, Response};
+use reqwest::{StatusCode, Url};
+
+use super::ReqwestError;
+
+const USER_AGENT: &str = concat!("wikipedia-network/", env!("CARGO_PKG_VERSION"));
+
+/// The delay between requests of a [RateLimitedSource] unless set otherwise
+const DEFAULT_MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+const DESCRIPTIVE_USER_AGENT: &str = concat!(
+    "wikipedia-network/",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/WaffleSoul4/wikipedia-network)"
+);
+
+/// The [Fetcher] used by pages that weren't given one, shared so that its rate limit applies to all of them
+pub(crate) static DEFAULT_FETCHER: LazyLock<Fetcher> = LazyLock::new(|| {
+    FetchConfig::default()
+        .build()
+        .expect("Failed to build the default fetcher")
+});
+
+/// The non-blocking client used by the `_async` methods of [Page](crate::Page)
+#[cfg(feature = "async")]
+pub(crate) static DEFAULT_ASYNC_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    let config = FetchConfig::default();
+
+    reqwest::Client::builder()
+        .user_agent(config.user_agent.as_str())
+        .timeout(config.timeout)
+        .build()
+        .expect("Failed to build the default async client")
+});
+
+/// The error a [BodySource] fails with, a [reqwest::Error] for a [Fetcher]
+pub type SourceError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Where the bodies of pages are loaded from, see [Page::with_source](crate::Page::with_source)
+///
+/// [Fetcher] is the implementation pages use by default, fetching them from Wikipedia. Implement this to load
+/// pages from somewhere else, like canned HTML in tests or a local copy of Wikipedia
+pub trait BodySource: std::fmt::Debug + Send + Sync {
+    /// Fetch the body of the page at `url`
+    fn fetch(&self, url: &Url) -> Result<String, SourceError>;
+}
+
+/// The number of temporary files [CachedSource]s have written so far in this process, to give each a unique name
+static TEMPORARY_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// A [BodySource] keeping a copy of every body it loads in a directory, so later loads of the same url read it
+/// from disk instead of fetching it again
+///
+/// Bodies are stored in one file per url, and can be given a time to live after which they're fetched again. The
+/// cache is never cleaned up, delete the directory to clear it. Failing to read or store a body isn't an error, the
+/// body is fetched and returned anyway
+#[derive(Debug)]
+pub struct CachedSource {
+    source: Arc<dyn BodySource>,
+    dir: PathBuf,
+    ttl: Option<Duration>,
+}
+
+impl CachedSource {
+    /// Create a new [CachedSource] storing bodies in `dir` and loading them from `source` on a cache miss
+    ///
+    /// The directory is created when the first body is stored
+    pub fn new<P: Into<PathBuf>>(dir: P, source: Arc<dyn BodySource>) -> Self {
+        CachedSource {
+            source,
+            dir: dir.into(),
+            ttl: None,
+        }
+    }
+
+    /// Set how long a stored body is used before it's fetched again, or `None` to keep using it forever
+    pub fn ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Get the path of the file the body of `url` is stored in, named after a hash of the url
+    fn path(&self, url: &Url) -> PathBuf {
+        // FNV-1a, since the hashers in std aren't guaranteed to be stable between releases
+        let hash = url.as_str().bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+
+        self.dir.join(format!("{hash:016x}.html"))
+    }
+
+    /// Read the stored body at `path`, if there is one that hasn't expired
+    fn read(&self, path: &Path) -> io::Result<Option<String>> {
+        let modified = match fs::metadata(path) {
+            Ok(metadata) => metadata.modified()?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let expired = self
+            .ttl
+            .is_some_and(|ttl| modified.elapsed().map_or(true, |age| age >= ttl));
+
+        if expired {
+            return Ok(None);
+        }
+
+        fs::read_to_string(path).map(Some)
+    }
+
+    /// Store `body` at `path`, writing it to a temporary file first so that a partly written body is never read
+    ///
+    /// Each write gets its own temporary file, so threads and processes storing the same url at once don't clobber
+    /// each other's, and the last rename wins
+    fn write(&self, path: &Path, body: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        let count = TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed);
+        let temporary = path.with_extension(format!("{}.{count}.tmp", std::process::id()));
+
+        let result = fs::write(&temporary, body).and_then(|_| fs::rename(&temporary, path));
+
+        if result.is_err() {
+            let _ = fs::remove_file(&temporary);
+        }
+
+        result
+    }
+}
+
+impl BodySource for CachedSource {
+    fn fetch(&self, url: &Url) -> Result<String, SourceError> {
+        let path = self.path(url);
+
+        // A cache that can't be read is treated like a miss, fetching the body is all it costs
+        if let Ok(Some(body)) = self.read(&path) {
+            return Ok(body);
+        }
+
+        let body = self.source.fetch(url)?;
+
+        let _ = self.write(&path, &body);
+
+        Ok(body)
+    }
+}
+
+/// A [BodySource] waiting between the requests it passes on to another source, to stay polite to Wikipedia when
+/// loading bodies from a source without a rate limit of its own
+///
+/// Pages found through a page's connections share its source, so giving the first page of a crawl (like the start of
+/// `WikipediaGraph::crawl_from`) a [RateLimitedSource] throttles the whole crawl. Wrap it in a [CachedSource]
+/// rather than the other way around to only wait on cache misses. [Fetcher]s have this built in, see
+/// [FetchConfig::min_interval]
+#[derive(Debug)]
+pub struct RateLimitedSource {
+    source: Arc<dyn BodySource>,
+    min_interval: Duration,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl RateLimitedSource {
+    /// Create a new [RateLimitedSource] starting requests to `source` at least 100ms apart
+    pub fn new(source: Arc<dyn BodySource>) -> Self {
+        RateLimitedSource {
+            source,
+            min_interval: DEFAULT_MIN_INTERVAL,
+            last_request: Mutex::new(None),
+        }
+    }
+
+    /// Set the minimum delay between the start of two requests
+    pub fn min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+}
+
+impl BodySource for RateLimitedSource {
+    fn fetch(&self, url: &Url) -> Result<String, SourceError> {
+        wait_for_interval(&self.last_request, self.min_interval);
+
+        self.source.fetch(url)
+    }
+}
+
+/// Block until at least `min_interval` has passed since `last_request`, then record the start of a new request
+///
+/// The lock is held while waiting, so requests from several threads are spaced out one after another
+fn wait_for_interval(last_request: &Mutex<Option<Instant>>, min_interval: Duration) {
+    if min_interval.is_zero() {
+        return;
+    }
+
+    let mut last_request = last_request.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(last) = *last_request {
+        let elapsed = last.elapsed();
+
+        if elapsed < min_interval {
+            thread::sleep(min_interval - elapsed);
+        }
+    }
+
+    *last_request = Some(Instant::now());
+}
+
+/// A preset of bundled [FetchConfig] defaults
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FetchProfile {
+    /// 1 request per second, a descriptive user agent, retries with backoff and `maxlag` on API requests,
+    /// following the [Wikimedia API etiquette]
+    ///
+    /// [Wikimedia API etiquette]: https://www.mediawiki.org/wiki/API:Etiquette
+    Polite,
+    /// No rate limit and a single retry
+    #[default]
+    Default,
+    /// No rate limit, no retries and a short timeout
+    Aggressive,
+}
+
+/// Settings for fetching pages, starting from a [FetchProfile] with individual fields overridable afterwards
+#[derive(Debug, Clone)]
+pub struct FetchConfig {
+    user_agent: String,
+    timeout: Duration,
+    max_retries: u32,
+    retry_backoff: Duration,
+    min_interval: Duration,
+    maxlag: Option<u32>,
+    max_connections_per_host: Option<usize>,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self::from_profile(FetchProfile::Default)
+    }
+}
+
+impl FetchConfig {
+    /// Create a new [FetchConfig] from the defaults of a [FetchProfile]
+    pub fn from_profile(profile: FetchProfile) -> Self {
+        match profile {
+            FetchProfile::Polite => FetchConfig {
+                user_agent: DESCRIPTIVE_USER_AGENT.to_string(),
+                timeout: Duration::from_secs(30),
+                max_retries: 3,
+                retry_backoff: Duration::from_secs(1),
+                min_interval: Duration::from_secs(1),
+                maxlag: Some(5),
+                max_connections_per_host: Some(2),
+            },
+            FetchProfile::Default => FetchConfig {
+                user_agent: USER_AGENT.to_string(),
+                timeout: Duration::from_secs(30),
+                max_retries: 1,
+                retry_backoff: Duration::from_millis(500),
+                min_interval: Duration::ZERO,
+                maxlag: None,
+                max_connections_per_host: None,
+            },
+            FetchProfile::Aggressive => FetchConfig {
+                user_agent: USER_AGENT.to_string(),
+                timeout: Duration::from_secs(10),
+                max_retries: 0,
+                retry_backoff: Duration::ZERO,
+                min_interval: Duration::ZERO,
+                maxlag: None,
+                max_connections_per_host: None,
+            },
+        }
+    }
+
+    /// Set the `User-Agent` header sent with every request
+    pub fn user_agent<T: std::fmt::Display>(mut self, user_agent: T) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Set the timeout for each request, from connecting until the body has been read
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set how many times a request is retried after a timeout, connection failure or `429`/`5xx` response
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the delay before the first retry, doubling on each retry after that
+    pub fn retry_backoff(mut self, retry_backoff: Duration) -> Self {
+        self.retry_backoff = retry_backoff;
+        self
+    }
+
+    /// Set the minimum delay between the start of two requests made through the same [Fetcher]
+    pub fn min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Set the [`maxlag`] parameter sent with API requests, or `None` to not send it
+    ///
+    /// [`maxlag`]: https://www.mediawiki.org/wiki/Manual:Maxlag_parameter
+    pub fn maxlag(mut self, maxlag: Option<u32>) -> Self {
+        self.maxlag = maxlag;
+        self
+    }
+
+    /// Set the most requests a [Fetcher] has open to a single host at once, or `None` for no limit
+    ///
+    /// This also caps the idle connections kept in the client's pool for each host. Requests over the limit
+    /// block until an earlier request to the same host has finished, so when fetching in parallel with a
+    /// concurrency cap of your own the effective number of connections is the lower of the two: a thread pool
+    /// larger than this limit only adds threads waiting on the [Fetcher]. A streamed body (see
+    /// [Page::stream_connections](crate::Page::stream_connections)) holds its slot until the whole body has been
+    /// read or the stream is dropped
+    ///
+    /// The limit only covers requests sent through the [Fetcher]. With the `async` feature, the `_async` methods of
+    /// pages without a fetcher or source of their own use a shared non-blocking client that isn't limited at all,
+    /// so cap the number of those in flight yourself
+    pub fn max_connections_per_host(mut self, max_connections_per_host: Option<usize>) -> Self {
+        self.max_connections_per_host = max_connections_per_host;
+        self
+    }
+
+    /// Get how many times a request is retried after a transient failure
+    pub fn get_max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Get the delay before the first retry
+    pub fn get_retry_backoff(&self) -> Duration {
+        self.retry_backoff
+    }
+
+    /// Get the [`maxlag`] parameter sent with API requests
+    ///
+    /// [`maxlag`]: https://www.mediawiki.org/wiki/Manual:Maxlag_parameter
+    pub fn get_maxlag(&self) -> Option<u32> {
+        self.maxlag
+    }
+
+    /// Build a [Fetcher] using this config
+    pub fn build(self) -> Result<Fetcher, ReqwestError> {
+        let mut builder = Client::builder()
+            .user_agent(self.user_agent.as_str())
+            .timeout(self.timeout);
+
+        if let Some(max) = self.max_connections_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+
+        Ok(self.build_with_client(builder.build()?))
+    }
+    /// Build a [Fetcher] sending its requests through an already configured [Client]
+    ///
+    /// The user agent, timeout and connection pool of `client` are used instead of the ones in this config, only
+    /// the rate limit, retries and per-host connection limit still apply. Wikipedia asks crawlers to send a
+    /// descriptive `User-Agent` with contact details (see the [User-Agent policy]), which [FetchProfile::Polite]
+    /// does by default, so set one on `client` as well
+    ///
+    /// [User-Agent policy]: https://meta.wikimedia.org/wiki/User-Agent_policy
+    pub fn build_with_client(self, client: Client) -> Fetcher {
+        Fetcher {
+            client,
+            config: self,
+            last_request: Mutex::new(None),
+            connections: Arc::new(ConnectionLimit::default()),
+        }
+    }
+}
+
+/// A [Client] paired with a [FetchConfig], applying its rate limit and retries to every request
+///
+/// The rate limit is tracked per [Fetcher], so share one (e.g. in an [Arc](std::sync::Arc)) between all of the
+/// pages of a crawl to throttle the crawl as a whole
+#[derive(Debug)]
+pub struct Fetcher {
+    client: Client,
+    config: FetchConfig,
+    last_request: Mutex<Option<Instant>>,
+    connections: Arc<ConnectionLimit>,
+}
+
+impl BodySource for Fetcher {
+    fn fetch(&self, url: &Url) -> Result<String, SourceError> {
+        Ok(self.get_text(url.clone())?)
+    }
+}
+
+/// The number of requests a [Fetcher] has open to each host
+#[derive(Debug, Default)]
+struct ConnectionLimit {
+    open: Mutex<HashMap<String, usize>>,
+    closed: Condvar,
+}
+
+/// A slot in the per-host connection limit of a [Fetcher], freed when dropped
+///
+/// It shares the count with the [Fetcher] rather than borrowing it, so a streamed body can keep its slot
+struct ConnectionPermit {
+    connections: Arc<ConnectionLimit>,
+    host: Option<String>,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        let Some(host) = &self.host else {
+            return;
+        };
+
+        let mut open = self.connections.open.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(count) = open.get_mut(host) {
+            *count -= 1;
+        }
+
+        self.connections.closed.notify_all();
+    }
+}
+
+/// The body of a [Response] read as it arrives, holding its [Fetcher]'s per-host connection slot until dropped
+pub(crate) struct StreamedBody {
+    response: Response,
+    _permit: ConnectionPermit,
+}
+
+impl io::Read for StreamedBody {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.response.read(buf)
+    }
+}
+
+impl Fetcher {
+    /// Get the [FetchConfig] the [Fetcher] was built with
+    pub fn get_config(&self) -> &FetchConfig {
+        &self.config
+    }
+
+    /// Send a `GET` request to `url`, waiting for the rate limit and retrying transient failures
+    ///
+    /// Error statuses like `404 Not Found`, or `429 Too Many Requests` once the retries run out, fail with the
+    /// status rather than returning the response. The per-host connection slot is only held until the response
+    /// headers arrive, so prefer [Fetcher::get_text] unless the body needs to be streamed
+    pub fn get(&self, url: Url) -> Result<Response, ReqwestError> {
+        let _permit = self.acquire_connection(&url);
+
+        self.send(url)
+    }
+
+    /// Send a `GET` request to `url` and read the whole body as text
+    pub fn get_text(&self, url: Url) -> Result<String, ReqwestError> {
+        let _permit = self.acquire_connection(&url);
+
+        self.send(url)?.text()
+    }
+
+    /// Send a `GET` request to `url` like [Fetcher::get], keeping the per-host connection slot until the body is
+    /// dropped rather than only until the headers arrive
+    pub(crate) fn get_streamed(&self, url: Url) -> Result<StreamedBody, ReqwestError> {
+        let permit = self.acquire_connection(&url);
+
+        Ok(StreamedBody {
+            response: self.send(url)?,
+            _permit: permit,
+        })
+    }
+
+    /// Block until the host of `url` is below `max_connections_per_host`, then take a slot
+    fn acquire_connection(&self, url: &Url) -> ConnectionPermit {
+        let (Some(max), Some(host)) = (self.config.max_connections_per_host, url.host_str()) else {
+            return ConnectionPermit {
+                connections: self.connections.clone(),
+                host: None,
+            };
+        };
+
+        let mut open = self.connections.open.lock().unwrap_or_else(|e| e.into_inner());
+
+        while open.get(host).is_some_and(|count| *count >= max.max(1)) {
+            open = self.connections.closed.wait(open).unwrap_or_else(|e| e.into_inner());
+        }
+
+        *open.entry(host.to_string()).or_default() += 1;
+
+        ConnectionPermit {
+            connections: self.connections.clone(),
+            host: Some(host.to_string()),
+        }
+    }
+
+    /// Send the request, waiting for the rate limit and retrying transient failures, and fail on error statuses
+    fn send(&self, url: Url) -> Result<Response, ReqwestError> {
+        let mut attempt = 0;
+
+        loop {
+            self.wait_for_rate_limit();
+
+            let result = self.client.get(url.clone()).send();
+
+            let retryable = match &result {
+                Ok(response) => {
+                    response.status() == StatusCode::TOO_MANY_REQUESTS
+                        || response.status().is_server_error()
+                }
+                Err(e) => e.is_timeout() || e.is_connect(),
+            };
+
+            if !retryable {
+                return result?.error_for_status();
+            }
+
+            if attempt >= self.config.max_retries {
+                return result?.error_for_status();
+            }
+
+            thread::sleep(self.config.retry_backoff * 2u32.saturating_pow(attempt));
+            attempt += 1;
+        }
+    }
+
+    /// Block until at least `min_interval` has passed since the previous request
+    fn wait_for_rate_limit(&self) {
+        wait_for_interval(&self.last_request, self.config.min_interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use std::{env, fs, thread};
+
+    use reqwest::blocking::Client;
+    use reqwest::{StatusCode, Url};
+
+    use super::{BodySource, CachedSource, FetchConfig, FetchProfile, RateLimitedSource};
+    use crate::tests::MockSource;
+    use crate::{Page, WikipediaUrl};
+
+    #[test]
+    fn test_profile_override() {
+        let config = FetchConfig::from_profile(FetchProfile::Polite).maxlag(None);
+
+        assert_eq!(config.min_interval, Duration::from_secs(1));
+        assert_eq!(config.get_maxlag(), None);
+        assert!(config.user_agent.contains("github.com"));
+    }
+
+    #[test]
+    fn test_connection_limit_per_host() {
+        let fetcher = FetchConfig::default()
+            .max_connections_per_host(Some(1))
+            .build()
+            .unwrap();
+
+        let waffle = Url::parse("https://en.wikipedia.org/wiki/Waffle").unwrap();
+        let api = Url::parse("https://www.wikidata.org/w/api.php").unwrap();
+
+        let permit = fetcher.acquire_connection(&waffle);
+        let _other_host = fetcher.acquire_connection(&api);
+
+        thread::scope(|scope| {
+            let waiting = scope.spawn(|| {
+                let _permit = fetcher.acquire_connection(&waffle);
+                Instant::now()
+            });
+
+            thread::sleep(Duration::from_millis(50));
+            let released = Instant::now();
+            drop(permit);
+
+            assert!(waiting.join().unwrap() >= released);
+        });
+    }
+
+    #[test]
+    fn test_streamed_body_holds_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/wiki/Waffle", listener.local_addr().unwrap())).unwrap();
+        let host = url.host_str().unwrap().to_string();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 1024]);
+
+            let body = "<title>Waffle - Wikipedia</title>";
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        });
+
+        let fetcher = FetchConfig::default()
+            .max_connections_per_host(Some(1))
+            .build_with_client(Client::new());
+        let open = |fetcher: &super::Fetcher| fetcher.connections.open.lock().unwrap().get(&host).copied();
+
+        let mut body = fetcher.get_streamed(url).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(open(&fetcher), Some(1));
+
+        let mut text = String::new();
+        body.read_to_string(&mut text).unwrap();
+
+        assert_eq!(text, "<title>Waffle - Wikipedia</title>");
+        assert_eq!(open(&fetcher), Some(1));
+
+        drop(body);
+
+        assert_eq!(open(&fetcher), Some(0));
+    }
+
+    #[test]
+    fn test_rate_limit_interval() {
+        let fetcher = FetchConfig::default()
+            .min_interval(Duration::from_millis(50))
+            .build()
+            .unwrap();
+
+        let start = Instant::now();
+
+        fetcher.wait_for_rate_limit();
+        fetcher.wait_for_rate_limit();
+
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_client_timeout() {
+        // Accept connections but never answer them
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/wiki/Waffle", listener.local_addr().unwrap())).unwrap();
+
+        let client = Client::builder().timeout(Duration::from_millis(1)).build().unwrap();
+        let fetcher = FetchConfig::default().max_retries(0).build_with_client(client);
+
+        let start = Instant::now();
+        let error = fetcher.get_text(url).unwrap_err();
+
+        assert!(error.is_timeout());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_get_text_error_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/wiki/Waffle", listener.local_addr().unwrap())).unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 1024]);
+
+            let body = "<title>Not Found</title>";
+            write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        });
+
+        let fetcher = FetchConfig::default().build_with_client(Client::new());
+        let error = fetcher.get_text(url).unwrap_err();
+
+        assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_cached_source() {
+        let dir = env::temp_dir().join(format!("wikipedia-network-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let source = MockSource::new("<title>Waffle - Wikipedia</title>");
+        let cached: Arc<dyn BodySource> = Arc::new(CachedSource::new(&dir, source.clone()));
+        let url = WikipediaUrl::from_path("/wiki/Waffle").unwrap();
+
+        let mut first = Page::with_source(url.clone(), cached.clone());
+        first.load_body().unwrap();
+
+        let mut second = Page::with_source(url.clone(), cached);
+        second.load_body().unwrap();
+
+        assert_eq!(source.fetched().len(), 1);
+        assert_eq!(second.get_title().unwrap(), "Waffle");
+
+        let expiring: Arc<dyn BodySource> = Arc::new(CachedSource::new(&dir, source.clone()).ttl(Some(Duration::ZERO)));
+        Page::with_source(url, expiring).load_body().unwrap();
+
+        assert_eq!(source.fetched().len(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cached_source_concurrent_and_failed_writes() {
+        let dir = env::temp_dir().join(format!("wikipedia-network-cache-concurrent-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let source = MockSource::new("<title>Waffle - Wikipedia</title>");
+        let cached = CachedSource::new(&dir, source.clone());
+        let url = Url::parse("https://en.wikipedia.org/wiki/Waffle").unwrap();
+
+        thread::scope(|scope| {
+            let fetches: Vec<_> = (0..8).map(|_| scope.spawn(|| cached.fetch(&url))).collect();
+
+            for fetch in fetches {
+                assert_eq!(fetch.join().unwrap().unwrap(), "<title>Waffle - Wikipedia</title>");
+            }
+        });
+
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // A file where the cache directory should be makes every read and write fail
+        let blocked = dir.join("blocked");
+        fs::write(&blocked, "").unwrap();
+
+        let uncached = CachedSource::new(&blocked, source.clone());
+
+        assert_eq!(uncached.fetch(&url).unwrap(), "<title>Waffle - Wikipedia</title>");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rate_limited_source() {
+        let source = MockSource::new("<title>Waffle - Wikipedia</title>");
+        let limited: Arc<dyn BodySource> =
+            Arc::new(RateLimitedSource::new(source.clone()).min_interval(Duration::from_millis(50)));
+
+        let mut waffle = Page::with_source(WikipediaUrl::from_path("/wiki/Waffle").unwrap(), limited.clone());
+        let mut belgium = Page::with_source(WikipediaUrl::from_path("/wiki/Belgium").unwrap(), limited);
+
+        let start = Instant::now();
+
+        waffle.load_body().unwrap();
+        belgium.load_body().unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(source.fetched().len(), 2);
+    }
+}
@@ -0,0 +1,421 @@
+//! A small HTML scanner for the few elements the crate reads out of a page body
+//!
+//! This only understands as much HTML as Wikipedia pages need: start tags with quoted or unquoted attributes and
+//! character references in attribute values and text, while skipping comments and `<script>` and `<style>` bodies.
+//! It never needs the whole document to be well formed, so it also works on fragments like chunks of a streamed
+//! body
+
+/// A start tag found in the HTML, with its raw attributes
+struct Tag<'a> {
+    name: &'a str,
+    attributes: &'a str,
+    /// The byte offset just past the end of the tag
+    end: usize,
+}
+
+impl Tag<'_> {
+    /// Get the value of the attribute `name`, with character references decoded
+    fn attribute(&self, name: &str) -> Option<String> {
+        let mut rest = self.attributes;
+
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+
+            if rest.is_empty() {
+                return None;
+            }
+
+            let name_end = rest
+                .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '/')
+                .unwrap_or(rest.len());
+            let attribute_name = &rest[..name_end];
+            rest = rest[name_end..].trim_start();
+
+            let value = match rest.strip_prefix('=') {
+                Some(value) => {
+                    let value = value.trim_start();
+
+                    let (raw, remaining) = match value.chars().next() {
+                        Some(quote @ ('"' | '\'')) => {
+                            let value = &value[1..];
+                            let end = value.find(quote).unwrap_or(value.len());
+
+                            (&value[..end], value.get(end + 1..).unwrap_or(""))
+                        }
+                        _ => {
+                            let end = value.find(|c: char| c.is_ascii_whitespace()).unwrap_or(value.len());
+
+                            (&value[..end], &value[end..])
+                        }
+                    };
+
+                    rest = remaining;
+                    raw
+                }
+                None => "",
+            };
+
+            if attribute_name.eq_ignore_ascii_case(name) {
+                return Some(decode_entities(value));
+            }
+        }
+    }
+}
+
+/// Elements whose contents are raw text rather than markup, so anything that looks like a tag inside them is skipped
+const RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+
+/// Iterate over the start tags named `name` in `html`
+///
+/// Comments and the contents of `<script>` and `<style>` elements are skipped, as are the attributes of other tags,
+/// so markup inside them is never mistaken for a tag. Scanning stops at the first piece of markup that isn't closed
+fn tags<'a>(html: &'a str, name: &'a str) -> impl Iterator<Item = Tag<'a>> + 'a {
+    let mut position = 0;
+
+    std::iter::from_fn(move || {
+        loop {
+            let start = position + html.get(position..)?.find('<')?;
+            let (resume, tag) = markup_at(html, start)?;
+
+            position = resume;
+
+            if let Some(tag) = tag.filter(|tag| tag.name.eq_ignore_ascii_case(name)) {
+                return Some(tag);
+            }
+        }
+    })
+}
+
+/// Get the length of the longest prefix of `html` that doesn't end part way through a tag, a comment or a `<script>`
+/// or `<style>` element, so that a body can be scanned in pieces with the rest carried over to the next one
+pub(crate) fn complete_len(html: &str) -> usize {
+    let mut position = 0;
+
+    while let Some(start) = html[position..].find('<').map(|i| position + i) {
+        match markup_at(html, start) {
+            Some((resume, _)) => position = resume,
+            None => return start,
+        }
+    }
+
+    html.len()
+}
+
+/// Scan the markup starting at the `<` at byte `start` of `html`
+///
+/// Returns the offset to carry on scanning from and the start tag, if the markup is one, or `None` if the markup
+/// continues past the end of `html`. A `<` that doesn't start a tag or comment is skipped on its own
+fn markup_at(html: &str, start: usize) -> Option<(usize, Option<Tag<'_>>)> {
+    let rest = &html[start + 1..];
+
+    if "!--".starts_with(rest) {
+        return None;
+    }
+
+    if rest.starts_with("!--") {
+        let end = start + 1 + rest.find("-->")? + "-->".len();
+
+        return Some((end, None));
+    }
+
+    let name_len = rest
+        .find(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+        .unwrap_or(rest.len());
+    let name = &rest[..name_len];
+
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Some((start + 1, None));
+    }
+
+    let after_name = start + 1 + name_len;
+    let end = after_name + attributes_end(&html[after_name..])?;
+
+    let mut resume = end + 1;
+
+    if RAW_TEXT_ELEMENTS.iter().any(|element| name.eq_ignore_ascii_case(element)) {
+        resume += find_ignore_case(&html[resume..], &format!("</{name}"))?;
+    }
+
+    let tag = Tag {
+        name,
+        attributes: &html[after_name..end],
+        end: end + 1,
+    };
+
+    Some((resume, Some(tag)))
+}
+
+/// Find the `>` closing a start tag, skipping over any inside quoted attribute values
+///
+/// Quotes only open a value right after an `=`, like `title='Guns N&#39; Roses'`, so apostrophes in unquoted values
+/// don't. If a quoted value is never closed the tag ends at its first `>` instead, rather than swallowing the rest
+/// of the document like a browser would, so one broken tag doesn't hide every link after it
+fn attributes_end(attributes: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut after_equals = false;
+
+    for (i, c) in attributes.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') if after_equals => quote = Some(c),
+            (None, '>') => return Some(i),
+            (None, '=') => {
+                after_equals = true;
+                continue;
+            }
+            (None, c) if c.is_ascii_whitespace() && after_equals => continue,
+            _ => {}
+        }
+
+        after_equals = false;
+    }
+
+    attributes.find('>')
+}
+
+/// Find the byte offset of the first match of the ASCII `needle` in `haystack`, ignoring ASCII case
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Iterate over the `href` and `title` attributes of every `<a>` tag linking to a `/wiki/` path
+///
+/// The fragment is dropped from the path, and links without a title (like the ones wrapping images) are skipped
+pub(crate) fn wiki_links(html: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    tags(html, "a").filter_map(|tag| {
+        let href = tag.attribute("href")?;
+        let path = href.split('#').next().unwrap_or_default();
+
+        if !path.starts_with("/wiki/") || path.len() == "/wiki/".len() || path.contains('?') {
+            return None;
+        }
+
+        Some((path.to_string(), tag.attribute("title")?))
+    })
+}
+
+/// Iterate over the `href` of every `<a>` tag linking to an absolute `http` or `https` url
+///
+/// Protocol-relative hrefs like `//example.com/` are treated as `https`
+pub(crate) fn absolute_links(html: &str) -> impl Iterator<Item = String> + '_ {
+    tags(html, "a").filter_map(|tag| {
+        let href = tag.attribute("href")?;
+
+        if href.starts_with("//") {
+            return Some(format!("https:{href}"));
+        }
+
+        let scheme = href.get(..href.find("://")?)?;
+
+        (scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https")).then_some(href)
+    })
+}
+
+/// Get the text of the `<title>` element of a page, with the ` - Wikipedia` suffix (in any language) removed
+pub(crate) fn page_title(html: &str) -> Option<String> {
+    let tag = tags(html, "title").next()?;
+
+    let text = &html[tag.end..];
+    let text = &text[..find_ignore_case(text, "</title>")?];
+    let text = decode_entities(text.trim());
+
+    let suffix = [" - ", " \u{2013} ", " \u{2014} "]
+        .iter()
+        .filter_map(|separator| text.rfind(separator).map(|start| (start, start + separator.len())))
+        .max()
+        .filter(|(_, end)| text[*end..].starts_with("Wiki"));
+
+    let title = match suffix {
+        Some((start, _)) => text[..start].to_string(),
+        None => text,
+    };
+
+    Some(title).filter(|title| !title.is_empty())
+}
+
+/// Get the `href` of the `<link rel="canonical">` tag of a page, which points to the article a redirect leads to
+pub(crate) fn canonical_url(html: &str) -> Option<String> {
+    tags(html, "link")
+        .find(|tag| tag.attribute("rel").is_some_and(|rel| rel.eq_ignore_ascii_case("canonical")))?
+        .attribute("href")
+        .filter(|href| !href.is_empty())
+}
+
+/// Get the `content` of the `<meta>` tag with the `property` attribute `property`, like `og:image`
+pub(crate) fn meta_property(html: &str, property: &str) -> Option<String> {
+    tags(html, "meta")
+        .find(|tag| tag.attribute("property").is_some_and(|value| value == property))?
+        .attribute("content")
+        .filter(|content| !content.is_empty())
+}
+
+/// Decode the character references in text, like `&amp;`, `&#39;` or `&#x2013;`
+///
+/// Only numeric references and the handful of named ones MediaWiki emits are decoded, others are left as they are
+pub(crate) fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let reference = rest
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| Some((decode_reference(&rest[1..end])?, end)));
+
+        match reference {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+
+    decoded
+}
+
+/// Decode the name of a character reference, without the `&` and `;`
+fn decode_reference(name: &str) -> Option<char> {
+    let code = match name.strip_prefix('#') {
+        Some(number) => match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        },
+        None => match name {
+            "amp" => '&' as u32,
+            "lt" => '<' as u32,
+            "gt" => '>' as u32,
+            "quot" => '"' as u32,
+            "apos" => '\'' as u32,
+            "nbsp" => '\u{a0}' as u32,
+            "ndash" => '\u{2013}' as u32,
+            "mdash" => '\u{2014}' as u32,
+            _ => return None,
+        },
+    };
+
+    char::from_u32(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical_url, complete_len, decode_entities, meta_property, page_title, wiki_links};
+
+    #[test]
+    fn test_wiki_links() {
+        let html = concat!(
+            "<a href=\"/wiki/Bj%C3%B8rn_Borg\" title=\"Bjørn Borg\">Borg</a>",
+            "<a class=\"mw-redirect\" href='/wiki/Guns_N%27_Roses' title='Guns N&#39; Roses'>GNR</a>",
+            "<A HREF=\"/wiki/20th_century#Europe\" TITLE=\"20th century\">",
+            "<a href=\"/w/index.php?title=Waffle&amp;action=edit\" title=\"Edit\">edit</a>",
+            "<a href=\"/wiki/File:Waffle.jpg\" class=\"mw-file-description\"><img src=\"waffle.jpg\"></a>",
+            "<a href=\"https://example.com/wiki/Waffle\" title=\"Elsewhere\">",
+        );
+
+        assert_eq!(
+            wiki_links(html).collect::<Vec<_>>(),
+            vec![
+                ("/wiki/Bj%C3%B8rn_Borg".to_string(), "Bjørn Borg".to_string()),
+                ("/wiki/Guns_N%27_Roses".to_string(), "Guns N' Roses".to_string()),
+                ("/wiki/20th_century".to_string(), "20th century".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wiki_links_hostile_markup() {
+        let html = concat!(
+            "<!-- <a href=\"/wiki/Commented\" title=\"Commented\">hidden</a> -->",
+            "<script>document.write('<a href=\"/wiki/Scripted\" title=\"Scripted\">');</script>",
+            "<STYLE>a[title=\"<a href='/wiki/Styled' title='Styled'>\"] {}</STYLE>",
+            "<a href=\"/wiki/Greater\" title=\"a > b\" data-x='<a href=\"/wiki/Inner\" title=\"Inner\">'>",
+            "<A HREF=/wiki/Unquoted TITLE=Unquoted>",
+            "<a title=Don't href=/wiki/Apostrophe>",
+            "<a href=\"/wiki/Unclosed title=\"Unclosed>",
+            "<a href=\"/wiki/After\" title=\"After\">",
+            "<!-- never closed <a href=\"/wiki/Trailing\" title=\"Trailing\">",
+        );
+
+        assert_eq!(
+            wiki_links(html).collect::<Vec<_>>(),
+            vec![
+                ("/wiki/Greater".to_string(), "a > b".to_string()),
+                ("/wiki/Unquoted".to_string(), "Unquoted".to_string()),
+                ("/wiki/Apostrophe".to_string(), "Don't".to_string()),
+                ("/wiki/After".to_string(), "After".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            wiki_links("<img alt=\"never closed><a href=/wiki/Recovered title=Recovered>").collect::<Vec<_>>(),
+            vec![("/wiki/Recovered".to_string(), "Recovered".to_string())]
+        );
+        assert_eq!(wiki_links("<a href=\"/wiki/Cut").count(), 0);
+        assert_eq!(wiki_links("<script><a href=\"/wiki/Unclosed\" title=\"Script\">").count(), 0);
+    }
+
+    #[test]
+    fn test_complete_len() {
+        assert_eq!(complete_len("<p>Waffles</p>"), 14);
+        assert_eq!(complete_len("<p>Waffle <a href=\"/wiki/Batter\""), 10);
+        assert_eq!(complete_len("<p>Waffle <!-- note <a> "), 10);
+        assert_eq!(complete_len("<p>Waffle <script>let a = '<a>';</scr"), 10);
+        assert_eq!(complete_len("<p>Waffle <"), 10);
+        assert_eq!(complete_len("<p>Waffle <!-"), 10);
+        assert_eq!(complete_len("<p>1 < 2 <!-- note --></p>"), 26);
+    }
+
+    #[test]
+    fn test_page_title() {
+        let html = "<head><meta charset=\"UTF-8\">\n<title>Guns N&#39; Roses - Wikipedia</title>\n</head>";
+
+        assert_eq!(page_title(html).as_deref(), Some("Guns N' Roses"));
+        assert_eq!(page_title("<title>Paris, Texas - Wikipedia</title>").as_deref(), Some("Paris, Texas"));
+        assert_eq!(page_title("<title>Rock - Paper \u{2013} Wikipedia</title>").as_deref(), Some("Rock - Paper"));
+        assert_eq!(page_title("<p>No title</p>"), None);
+        assert_eq!(page_title("<!-- <title>Fake - Wikipedia</title> --><TITLE>Real - Wikipedia</Title>").as_deref(), Some("Real"));
+    }
+
+    #[test]
+    fn test_canonical_url() {
+        let html = concat!(
+            "<link rel=\"stylesheet\" href=\"/w/load.php\">\n",
+            "<link rel=\"canonical\" href=\"https://en.wikipedia.org/wiki/United_States\">",
+        );
+
+        assert_eq!(canonical_url(html).as_deref(), Some("https://en.wikipedia.org/wiki/United_States"));
+        assert_eq!(canonical_url("<link rel=\"stylesheet\" href=\"/w/load.php\">"), None);
+    }
+
+    #[test]
+    fn test_meta_property() {
+        let html = concat!(
+            "<meta property=\"og:title\" content=\"Waffle - Wikipedia\">\n",
+            "<meta property=\"og:image\" content=\"https://upload.wikimedia.org/waffle.jpg?a=1&amp;b=2\">",
+        );
+
+        assert_eq!(meta_property(html, "og:image").as_deref(), Some("https://upload.wikimedia.org/waffle.jpg?a=1&b=2"));
+        assert_eq!(meta_property(html, "og:description"), None);
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("Fish &amp; chips &#x2013; &unknown; & more"), "Fish & chips – &unknown; & more");
+    }
+}
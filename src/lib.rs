@@ -6,7 +6,7 @@
 //!
 //! ```rust
 //! # use wikipedia_network::{Page, WikipediaUrl};
-//! # fn main() -> Result<(), reqwest::Error> {
+//! # fn main() -> Result<(), wikipedia_network::PageError> {
 //! let url = WikipediaUrl::from_path("/wiki/Waffles").unwrap(); // Parse the url
 //! let mut waffles_page = Page::new(url); // Initialize the page struct
 //! 
@@ -29,21 +29,215 @@
 //! ```
 //!
 
-// TODO:
-//     - Language support
-//     - Async (mmm...)
-
 #[cfg(feature = "petgraph")]
 pub mod petgraph;
 
+#[cfg(feature = "api")]
+pub mod api;
+
+pub mod fetch;
+
+mod html;
+
+#[cfg(feature = "async")]
+mod nonblocking;
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, Read};
+use std::sync::{Arc, LazyLock};
+
+use fetch::{BodySource, DEFAULT_FETCHER, Fetcher, SourceError};
+
+use percent_encoding::percent_decode_str;
 use regex::Regex;
 use reqwest::{IntoUrl, Url};
 use thiserror::Error;
 
 type ReqwestError = reqwest::Error;
 
-#[derive(Debug, Clone)]
+static PARAGRAPH_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<p(?:\s[^>]*)?>(.*?)</p>").expect("Paragraph regex failed to compile"));
+
+static TAG_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").expect("Tag regex failed to compile"));
+
+/// Matches reference markers like `[1]` or `[note 2]` left behind once the tags are stripped
+static REFERENCE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[(?:\d+|[a-z]+ \d+)\]").expect("Reference regex failed to compile"));
+
+/// Matches markup inside the article content that isn't part of the prose: styles, scripts, edit section links and
+/// reference or maintenance superscripts like `[1]` and `[citation needed]`
+static NON_PROSE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"(?s)<style[^>]*>.*?</style>|<script[^>]*>.*?</script>",
+        r#"|<span class="mw-editsection">.*?</span>\s*</span>"#,
+        r#"|<sup[^>]*\sclass="[^"]*(?:reference|noprint)[^"]*"[^>]*>.*?</sup>"#,
+    ))
+    .expect("Non-prose regex failed to compile")
+});
+
+/// Matches the contents of hatnotes, the "For other uses, see ..." notes at the top of an article or section
+static HATNOTE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)<div[^>]*\sclass="(?:[^"]*\s)?hatnote(?:\s[^"]*)?"[^>]*>(.*?)</div>"#)
+        .expect("Hatnote regex failed to compile")
+});
+
+/// Words that are commonly followed by a full stop without ending a sentence
+const ABBREVIATIONS: [&str; 22] = [
+    "Dr", "Mr", "Mrs", "Ms", "Prof", "St", "Mt", "Jr", "Sr", "Sgt", "Capt", "Gen", "Gov", "Rev", "Inc", "Ltd", "Co",
+    "No", "vs", "etc", "approx", "ca",
+];
+
+/// Title prefixes of list and index articles, see [WikipediaUrl::is_list_article]
+const LIST_ARTICLE_PREFIXES: [&str; 4] = ["List of ", "Lists of ", "Index of ", "Glossary of "];
+
+/// The id of the heading of the "See also" section, which runs until the next `<h2>`
+const SEE_ALSO_HEADING_ID: &str = "id=\"See_also\"";
+
+/// Markup that only appears on disambiguation pages: the disambiguation box, and the link to the category all of
+/// them are in
+///
+/// The `mw-disambig` class isn't one of them, since it's also on links to disambiguation pages from normal articles
+const DISAMBIGUATION_MARKERS: [&str; 2] = ["id=\"disambigbox\"", "href=\"/wiki/Category:Disambiguation_pages\""];
+
+/// The id of the footer block listing the categories of a page
+const NORMAL_CATEGORIES_ID: &str = "id=\"mw-normal-catlinks\"";
+
+/// The id of the footer block listing the hidden categories of a page, see [Page::get_hidden_categories]
+const HIDDEN_CATEGORIES_ID: &str = "id=\"mw-hidden-catlinks\"";
+
+/// The language of the Wikipedia used when none is given
+const DEFAULT_LANG: &str = "en";
+
+/// The local names of the [Namespace]s on some of the larger Wikipedias, lowercased with underscores, used on top of
+/// the canonical English names every Wikipedia accepts
+const LOCAL_NAMESPACES: [(&str, &[(&str, Namespace)]); 7] = [
+    (
+        "de",
+        &[
+            ("benutzer", Namespace::User),
+            ("benutzerin", Namespace::User),
+            ("datei", Namespace::File),
+            ("bild", Namespace::File),
+            ("medium", Namespace::Media),
+            ("vorlage", Namespace::Template),
+            ("hilfe", Namespace::Help),
+            ("kategorie", Namespace::Category),
+            ("modul", Namespace::Module),
+            ("spezial", Namespace::Special),
+        ],
+    ),
+    (
+        "fr",
+        &[
+            ("utilisateur", Namespace::User),
+            ("utilisatrice", Namespace::User),
+            ("wikipédia", Namespace::Wikipedia),
+            ("fichier", Namespace::File),
+            ("média", Namespace::Media),
+            ("modèle", Namespace::Template),
+            ("aide", Namespace::Help),
+            ("catégorie", Namespace::Category),
+            ("portail", Namespace::Portal),
+            ("spécial", Namespace::Special),
+        ],
+    ),
+    (
+        "es",
+        &[
+            ("usuario", Namespace::User),
+            ("usuaria", Namespace::User),
+            ("archivo", Namespace::File),
+            ("imagen", Namespace::File),
+            ("medio", Namespace::Media),
+            ("plantilla", Namespace::Template),
+            ("ayuda", Namespace::Help),
+            ("categoría", Namespace::Category),
+            ("módulo", Namespace::Module),
+            ("especial", Namespace::Special),
+        ],
+    ),
+    (
+        "it",
+        &[
+            ("utente", Namespace::User),
+            ("immagine", Namespace::File),
+            ("aiuto", Namespace::Help),
+            ("categoria", Namespace::Category),
+            ("portale", Namespace::Portal),
+            ("modulo", Namespace::Module),
+            ("speciale", Namespace::Special),
+        ],
+    ),
+    (
+        "nl",
+        &[
+            ("gebruiker", Namespace::User),
+            ("bestand", Namespace::File),
+            ("afbeelding", Namespace::File),
+            ("sjabloon", Namespace::Template),
+            ("categorie", Namespace::Category),
+            ("portaal", Namespace::Portal),
+            ("speciaal", Namespace::Special),
+        ],
+    ),
+    (
+        "pt",
+        &[
+            ("usuário", Namespace::User),
+            ("utilizador", Namespace::User),
+            ("ficheiro", Namespace::File),
+            ("arquivo", Namespace::File),
+            ("imagem", Namespace::File),
+            ("multimédia", Namespace::Media),
+            ("predefinição", Namespace::Template),
+            ("ajuda", Namespace::Help),
+            ("categoria", Namespace::Category),
+            ("módulo", Namespace::Module),
+            ("especial", Namespace::Special),
+        ],
+    ),
+    (
+        "pl",
+        &[
+            ("wikipedysta", Namespace::User),
+            ("wikipedystka", Namespace::User),
+            ("użytkownik", Namespace::User),
+            ("plik", Namespace::File),
+            ("grafika", Namespace::File),
+            ("szablon", Namespace::Template),
+            ("pomoc", Namespace::Help),
+            ("kategoria", Namespace::Category),
+            ("moduł", Namespace::Module),
+            ("specjalna", Namespace::Special),
+        ],
+    ),
+];
+
+/// The words the talk namespaces of some of the larger Wikipedias are named with, either alone (`Diskussion`) or
+/// before or after the name of the subject namespace (`Discussion utilisateur`, `Benutzer Diskussion`)
+const LOCAL_TALK_WORDS: [(&str, &[&str]); 7] = [
+    ("de", &["diskussion"]),
+    ("fr", &["discussion"]),
+    ("es", &["discusión"]),
+    ("it", &["discussione", "discussioni"]),
+    ("nl", &["overleg"]),
+    ("pt", &["discussão"]),
+    ("pl", &["dyskusja"]),
+];
+
+/// Markup that only appears after the article content, used to stop streaming a body early
+const CONTENT_END_MARKERS: [&str; 2] = ["id=\"catlinks\"", "class=\"printfooter\""];
+
+/// The number of bytes [ConnectionStream] reads from the body at a time
+const STREAM_CHUNK_LEN: usize = 16 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 /// A parser struct containing the [Url] of a Wikipedia page
+///
+/// Two [WikipediaUrl]s are equal when their [Url]s are, so compare [WikipediaUrl::normalized] copies to also
+/// match different spellings of the same article
 pub struct WikipediaUrl(Url);
 
 impl WikipediaUrl {
@@ -59,14 +253,37 @@ impl WikipediaUrl {
             }),
         }?;
 
-        // TODO: Multiple language support
+        if let Some(host) = url.host_str()
+            && host != "en.wikipedia.org"
+        {
+            return Err(WikipediaUrlInvalidError::InvalidHostError(
+                input_url.to_string(),
+            ));
+        }
 
-        if let Some(host) = url.host_str() {
-            if host != "en.wikipedia.org" {
-                return Err(WikipediaUrlInvalidError::InvalidHostError(
-                    input_url.to_string(),
-                ));
-            }
+        Ok(WikipediaUrl(url))
+    }
+
+    /// Creates a new [WikipediaUrl] on any language edition of Wikipedia, like `https://de.wikipedia.org/wiki/Waffel`
+    ///
+    /// The host has to be `xx.wikipedia.org`, where `xx` is a two or three letter language code
+    pub fn new_any_lang<T: IntoUrl + std::fmt::Display + Clone>(
+        input_url: T,
+    ) -> Result<Self, WikipediaUrlInvalidError> {
+        let url = match input_url.clone().into_url() {
+            Ok(t) => Ok(t),
+            Err(e) => Err(WikipediaUrlInvalidError::InvalidUrlError {
+                source: e,
+                invalid_url: input_url.to_string(),
+            }),
+        }?;
+
+        if let Some(host) = url.host_str()
+            && lang_from_host(host).is_none()
+        {
+            return Err(WikipediaUrlInvalidError::InvalidHostError(
+                input_url.to_string(),
+            ));
         }
 
         Ok(WikipediaUrl(url))
@@ -74,25 +291,261 @@ impl WikipediaUrl {
 
     #[doc = "Creates a new [WikipediaUrl] from the path of the url\nFor example: `https://en.wikipedia.org/wiki/Waffle` vs. `/wiki/Waffle`"]
     pub fn from_path<T: std::fmt::Display>(path: T) -> Result<Self, WikipediaUrlInvalidError> {
-        let mut wikipedia_host: String;
+        Self::from_path_with_lang(path, DEFAULT_LANG)
+    }
+
+    /// Creates a new [WikipediaUrl] from the path of the url on the Wikipedia of the language `lang`
+    ///
+    /// For example `/wiki/Waffel` with `de` for `https://de.wikipedia.org/wiki/Waffel`
+    pub fn from_path_with_lang<T: std::fmt::Display>(path: T, lang: &str) -> Result<Self, WikipediaUrlInvalidError> {
+        let mut wikipedia_host = format!("https://{lang}.wikipedia.org");
 
         let path = path.to_string();
 
-        if path.starts_with("/") || path.starts_with("\\") {
-            wikipedia_host = "https://en.wikipedia.org".to_string()
-        } else {
-            wikipedia_host = "https://en.wikipedia.org/".to_string()
+        if !(path.starts_with("/") || path.starts_with("\\")) {
+            wikipedia_host.push('/');
         }
 
         wikipedia_host.push_str(path.as_str());
 
-        WikipediaUrl::new(wikipedia_host)
+        WikipediaUrl::new_any_lang(wikipedia_host)
+    }
+
+    /// Creates a new [WikipediaUrl] from the title of an article, like `Belgian waffle`
+    ///
+    /// The title is slugified the way Wikipedia does: spaces become underscores, characters that aren't allowed in
+    /// a path are percent-encoded and the first letter is capitalized
+    pub fn from_title<T: std::fmt::Display>(title: T) -> Result<Self, WikipediaUrlInvalidError> {
+        let mut wiki_url = WikipediaUrl::from_path("/wiki/")?;
+
+        let slug = title.to_string().trim().replace(' ', "_");
+
+        wiki_url.0.set_path(&format!("/wiki/{slug}"));
+
+        Ok(wiki_url.normalized())
     }
 
     /// Get the raw [Url] from the [WikipediaUrl]
     pub fn get_url(&self) -> &Url {
         &self.0
     }
+
+    /// Get the language code of the Wikipedia the [WikipediaUrl] points to, like `en` or `de`
+    pub fn lang(&self) -> &str {
+        self.0.host_str().and_then(lang_from_host).unwrap_or(DEFAULT_LANG)
+    }
+
+    /// Get the [Namespace] of the page the [WikipediaUrl] points to, by the prefix names of its language, see
+    /// [Namespace::from_title_with_lang]
+    pub fn namespace(&self) -> Namespace {
+        Namespace::from_title_with_lang(&self.title_from_url(), self.lang())
+    }
+
+    /// Check if the [WikipediaUrl] points to a list or index article, like `List of waffle dishes`,
+    /// `Index of baking articles` or `Glossary of baking`
+    ///
+    /// These are structurally special hubs that link to many loosely related articles
+    pub fn is_list_article(&self) -> bool {
+        let title = self.title_from_url();
+
+        LIST_ARTICLE_PREFIXES.iter().any(|prefix| {
+            title
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        })
+    }
+
+    /// Get the title of the article from the url path, percent-decoded and with underscores replaced by spaces
+    ///
+    /// For example `/wiki/Cura%C3%A7ao` gives `Curaçao`. This is a fallback for when the body of the page hasn't
+    /// been loaded, the real title (see [Page::get_title]) can differ in letter case and punctuation
+    pub fn title_from_url(&self) -> String {
+        let path = percent_decode_str(self.0.path()).decode_utf8_lossy();
+
+        path.strip_prefix("/wiki/").unwrap_or(&path).replace('_', " ")
+    }
+
+    /// Get a normalized copy of the [WikipediaUrl], so that different spellings of the same article compare equal
+    ///
+    /// The query and fragment are dropped, the path is percent-decoded and re-encoded consistently, spaces become
+    /// underscores and the first letter of the article title is capitalized (Wikipedia treats it as case-insensitive)
+    pub fn normalized(&self) -> WikipediaUrl {
+        let mut url = self.0.clone();
+
+        url.set_query(None);
+        url.set_fragment(None);
+
+        let path = percent_decode_str(url.path()).decode_utf8_lossy().replace(' ', "_");
+
+        let path = match path.strip_prefix("/wiki/") {
+            Some(title) => {
+                let mut chars = title.chars();
+
+                match chars.next() {
+                    Some(first) => format!("/wiki/{}{}", first.to_uppercase(), chars.as_str()),
+                    None => path.clone(),
+                }
+            }
+            None => path.clone(),
+        };
+
+        url.set_path(&path);
+
+        WikipediaUrl(url)
+    }
+}
+
+/// Prints the full url, like `https://en.wikipedia.org/wiki/Waffle`
+impl std::fmt::Display for WikipediaUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Parses either a full url on any language edition of Wikipedia (see [WikipediaUrl::new_any_lang]) or the path of
+/// a url on English Wikipedia (see [WikipediaUrl::from_path])
+///
+/// ```rust
+/// # use wikipedia_network::WikipediaUrl;
+/// let url: WikipediaUrl = "/wiki/Waffle".parse().unwrap();
+///
+/// assert_eq!(url.to_string(), "https://en.wikipedia.org/wiki/Waffle");
+/// ```
+impl std::str::FromStr for WikipediaUrl {
+    type Err = WikipediaUrlInvalidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains("://") {
+            WikipediaUrl::new_any_lang(s)
+        } else {
+            WikipediaUrl::from_path(s)
+        }
+    }
+}
+
+/// Parses the url like [WikipediaUrl::from_str](std::str::FromStr::from_str)
+impl TryFrom<&str> for WikipediaUrl {
+    type Error = WikipediaUrlInvalidError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Serialized as the url string
+#[cfg(feature = "serde")]
+impl serde::Serialize for WikipediaUrl {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+/// Deserialized from a url string on any language edition of Wikipedia, see [WikipediaUrl::new_any_lang]
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for WikipediaUrl {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let url = String::deserialize(deserializer)?;
+
+        WikipediaUrl::new_any_lang(url.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Get the language code out of a `xx.wikipedia.org` host, if it is one
+fn lang_from_host(host: &str) -> Option<&str> {
+    host.strip_suffix(".wikipedia.org")
+        .filter(|lang| (2..=3).contains(&lang.len()) && lang.bytes().all(|b| b.is_ascii_lowercase()))
+}
+
+/// The [namespace] of a Wikipedia page, taken from the prefix of its title
+///
+/// [namespace]: https://en.wikipedia.org/wiki/Wikipedia:Namespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Namespace {
+    Article,
+    Talk,
+    User,
+    Wikipedia,
+    File,
+    MediaWiki,
+    Template,
+    Help,
+    Category,
+    Portal,
+    Draft,
+    TimedText,
+    Module,
+    Special,
+    Media,
+}
+
+impl Namespace {
+    /// Get the [Namespace] of an article title, treating unknown prefixes (like in `Star Wars: Episode IV`) as part of an article title
+    ///
+    /// This only knows the canonical English prefixes, which every Wikipedia accepts. Titles from other Wikipedias
+    /// mostly use local ones like `Kategorie:`, so use [Namespace::from_title_with_lang] for those
+    pub fn from_title(title: &str) -> Namespace {
+        Self::from_title_with_lang(title, DEFAULT_LANG)
+    }
+
+    /// Get the [Namespace] of a title from the Wikipedia in `lang`, knowing its local prefixes as well as the
+    /// canonical English ones
+    ///
+    /// Local prefixes are only known for a few of the larger Wikipedias (`de`, `fr`, `es`, `it`, `nl`, `pt` and
+    /// `pl`), on the others only the canonical ones are recognized
+    pub fn from_title_with_lang(title: &str, lang: &str) -> Namespace {
+        let Some((prefix, _)) = title.split_once(':') else {
+            return Namespace::Article;
+        };
+
+        let prefix = prefix.trim().replace(' ', "_").to_lowercase();
+
+        let local = LOCAL_NAMESPACES
+            .iter()
+            .find(|(local_lang, _)| *local_lang == lang)
+            .and_then(|(_, names)| names.iter().find(|(name, _)| *name == prefix));
+
+        if let Some((_, namespace)) = local {
+            return *namespace;
+        }
+
+        // A talk prefix is the talk word alone or next to the name of its subject namespace, so that an article
+        // like `Die Diskussion: Ein Roman` isn't taken for one
+        let is_local_talk = LOCAL_TALK_WORDS
+            .iter()
+            .find(|(local_lang, _)| *local_lang == lang)
+            .is_some_and(|(_, words)| {
+                words.iter().any(|word| {
+                    let subject = match prefix.strip_prefix(word).or_else(|| prefix.strip_suffix(word)) {
+                        Some(subject) => subject.trim_matches('_'),
+                        None => return false,
+                    };
+
+                    subject.is_empty() || Self::from_title_with_lang(&format!("{subject}:"), lang) != Namespace::Article
+                })
+            });
+
+        if is_local_talk {
+            return Namespace::Talk;
+        }
+
+        match prefix.as_str() {
+            "user" => Namespace::User,
+            "wikipedia" | "project" | "wp" => Namespace::Wikipedia,
+            "file" | "image" => Namespace::File,
+            "mediawiki" => Namespace::MediaWiki,
+            "template" => Namespace::Template,
+            "help" => Namespace::Help,
+            "category" => Namespace::Category,
+            "portal" => Namespace::Portal,
+            "draft" => Namespace::Draft,
+            "timedtext" => Namespace::TimedText,
+            "module" => Namespace::Module,
+            "special" => Namespace::Special,
+            "media" => Namespace::Media,
+            prefix if prefix == "talk" || prefix.ends_with("_talk") => Namespace::Talk,
+            _ => Namespace::Article,
+        }
+    }
 }
 
 /// This error covers all failures related to the parsing of a [WikipediaUrl]
@@ -107,12 +560,59 @@ pub enum WikipediaUrlInvalidError {
     },
 }
 
+/// This error covers all failures related to loading and parsing a [Page]
+#[derive(Error, Debug)]
+pub enum PageError {
+    #[error("request for the page failed: '{0}'")]
+    RequestError(#[from] ReqwestError),
+    #[error("no title was found on '{0}'")]
+    TitleNotFoundError(String),
+    #[error("failed to parse the page: {0}")]
+    ParseError(String),
+    #[error("loading the page from its source failed: '{0}'")]
+    SourceError(#[source] SourceError),
+}
+
+impl From<SourceError> for PageError {
+    /// Request failures are kept as [PageError::RequestError]
+    fn from(error: SourceError) -> Self {
+        match error.downcast::<ReqwestError>() {
+            Ok(error) => PageError::RequestError(*error),
+            Err(error) => PageError::SourceError(error),
+        }
+    }
+}
+
 /// A struct representing a Wikipedia page, optionally containing the title and body of the page
+///
+/// With the `serde` feature only the title and url are serialized, a deserialized page has no body and loads it
+/// through the default [Fetcher]
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Page {
     title: Option<String>,
     url: WikipediaUrl,
+    #[cfg_attr(feature = "serde", serde(skip))]
     body: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    fetcher: Option<Arc<Fetcher>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    source: Option<Arc<dyn BodySource>>,
+}
+
+/// Pages are compared by their [WikipediaUrl] only, so a loaded and unloaded copy of the same page are equal
+impl PartialEq for Page {
+    fn eq(&self, other: &Self) -> bool {
+        self.url == other.url
+    }
+}
+
+impl Eq for Page {}
+
+impl Hash for Page {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.url.hash(state);
+    }
 }
 
 impl Page {
@@ -127,33 +627,111 @@ impl Page {
             title: None,
             url,
             body: None,
+            fetcher: None,
+            source: None,
         }
     }
 
-    /// Load the body of the wikipedia page into the struct
-    pub fn load_body(&mut self) -> Result<(), ReqwestError> {
-        if self.body.is_some() {
-            return Ok(());
+    /// Create a new [Page] that loads its body through `fetcher`, see [fetch::FetchConfig]
+    ///
+    /// Pages found through this page's connections share the same [Fetcher], so its rate limit covers the whole crawl
+    pub fn with_fetcher(url: WikipediaUrl, fetcher: Arc<Fetcher>) -> Self {
+        Page {
+            fetcher: Some(fetcher),
+            ..Page::new(url)
+        }
+    }
+
+    /// Create a new [Page] that loads its body through `client`, with the rate limit and retries of the default
+    /// [fetch::FetchConfig]
+    ///
+    /// Use this to set your own `User-Agent`, timeouts or connection pool. Wikipedia asks crawlers to identify
+    /// themselves with a descriptive `User-Agent` including contact details, see the [User-Agent policy].
+    /// Like [Page::with_fetcher], pages found through this page's connections share the same client
+    ///
+    /// [User-Agent policy]: https://meta.wikimedia.org/wiki/User-Agent_policy
+    pub fn with_client(url: WikipediaUrl, client: reqwest::blocking::Client) -> Self {
+        Page::with_fetcher(url, Arc::new(fetch::FetchConfig::default().build_with_client(client)))
+    }
+
+    /// Create a new [Page] that loads its body from `source` instead of fetching it from Wikipedia, see
+    /// [BodySource]
+    ///
+    /// Pages found through this page's connections load their bodies from the same source. With the `api` feature
+    /// the responses of the MediaWiki API queries about the page are loaded from `source` as well
+    pub fn with_source(url: WikipediaUrl, source: Arc<dyn BodySource>) -> Self {
+        Page {
+            source: Some(source),
+            ..Page::new(url)
+        }
+    }
+
+    /// Get the [Fetcher] the page sends requests through
+    fn fetcher(&self) -> &Fetcher {
+        self.fetcher.as_deref().unwrap_or(&DEFAULT_FETCHER)
+    }
+
+    /// Get the [BodySource] the page loads its body from, its [Fetcher] unless it was given one
+    fn source(&self) -> &dyn BodySource {
+        match &self.source {
+            Some(source) => source.as_ref(),
+            None => self.fetcher(),
+        }
+    }
+
+    /// Get a copy of the page without the body, which can be large
+    fn without_body(&self) -> Page {
+        Page {
+            title: self.title.clone(),
+            url: self.url.clone(),
+            body: None,
+            fetcher: self.fetcher.clone(),
+            source: self.source.clone(),
+        }
+    }
+
+    /// Make pages found on this page use the same [Fetcher] and [BodySource] and point to the same language of
+    /// Wikipedia
+    fn inherit_wiki(&self, mut pages: Vec<Page>) -> Vec<Page> {
+        pages.iter_mut().for_each(|page| self.adopt(page));
+
+        pages
+    }
+
+    /// Make a single page found on this page use the same [Fetcher], [BodySource] and language, see
+    /// [Page::inherit_wiki]
+    fn adopt(&self, page: &mut Page) {
+        if let Some(fetcher) = &self.fetcher {
+            page.fetcher = Some(fetcher.clone());
         }
 
-        let url = self.get_url();
+        if let Some(source) = &self.source {
+            page.source = Some(source.clone());
+        }
 
-        let client = reqwest::blocking::Client::builder().user_agent("wikipedia-network/0.1.2").build()?;
+        if page.url.0.host_str() != self.url.0.host_str() {
+            let _ = page.url.0.set_host(self.url.0.host_str());
+        }
+    }
 
+    /// Load the body of the wikipedia page into the struct
+    pub fn load_body(&mut self) -> Result<(), PageError> {
+        if self.body.is_some() {
+            return Ok(());
+        }
 
-        self.body = Some(client.get(url.clone()).send()?.text()?);
+        self.body = Some(self.source().fetch(self.get_url())?);
 
         Ok(())
     }
 
     /// Get the body of the wikipedia page
-    fn get_body(&mut self) -> Result<&String, ReqwestError> {
+    fn get_body(&mut self) -> Result<&String, PageError> {
         self.load_body()?;
 
-        match &self.body {
-            Some(body) => Ok(body),
-            None => panic!("Failed to load body for an unknown reason"),
-        }
+        self.body
+            .as_ref()
+            .ok_or_else(|| PageError::ParseError(format!("the body of '{}' failed to load", self.url.get_url())))
     }
 
     /// Remove the body from the wikipedia page to preserve memory
@@ -162,31 +740,35 @@ impl Page {
     }
 
     /// Load the title of the Wikipedia page into the struct, loading the body as well if necessary
-    pub fn load_title(&mut self) -> Result<(), ReqwestError> {
+    pub fn load_title(&mut self) -> Result<(), PageError> {
         if self.title.is_some() {
             return Ok(());
         }
 
-        self.title = Some(Self::get_title_from_body(self.get_body()?)?);
+        let title = Self::get_title_from_body(self.get_body()?);
+
+        self.title = Some(title.ok_or_else(|| PageError::TitleNotFoundError(self.url.get_url().to_string()))?);
 
         Ok(())
     }
 
     /// Only load the title of the Wikipedia page if the body is loaded as well
-    pub fn try_load_title(&mut self) -> Result<(), ReqwestError> {
+    pub fn try_load_title(&mut self) -> Result<(), PageError> {
         if self.title.is_some() {
             return Ok(());
         }
 
         if let Some(body) = &self.body {
-            self.title = Some(Self::get_title_from_body(body)?)
+            let title = Self::get_title_from_body(body);
+
+            self.title = Some(title.ok_or_else(|| PageError::TitleNotFoundError(self.url.get_url().to_string()))?)
         }
 
         Ok(())
     }
 
     /// Get the title of the Wikipedia page, loading it and the body as well if necessary
-    pub fn get_title(&mut self) -> Result<String, ReqwestError> {
+    pub fn get_title(&mut self) -> Result<String, PageError> {
         self.load_title()?;
 
         Ok(self
@@ -196,33 +778,110 @@ impl Page {
     }
 
     /// Only get the title of the Wikipedia page if the title is already loaded
-    pub fn try_get_title(&mut self) -> Result<Option<String>, ReqwestError> {
+    pub fn try_get_title(&mut self) -> Result<Option<String>, PageError> {
         self.try_load_title()?;
 
         Ok(self.title.clone())
     }
 
+    /// Update the url of the page to the canonical url of the article, loading the body as well if necessary
+    ///
+    /// Wikipedia serves redirects like `/wiki/USA` with the body of the article they lead to, whose
+    /// `<link rel="canonical">` points to `/wiki/United_States`. Resolve redirects before comparing pages so that
+    /// the same article linked under several names is only counted once. The url is left unchanged if the body
+    /// has no canonical link
+    pub fn resolve_redirect(&mut self) -> Result<(), PageError> {
+        let Some(canonical) = html::canonical_url(self.get_body()?) else {
+            return Ok(());
+        };
+
+        let url = self
+            .get_url()
+            .join(&canonical)
+            .map_err(|e| PageError::ParseError(format!("invalid canonical url {canonical}: {e}")))?;
+
+        self.url = WikipediaUrl::new_any_lang(url)
+            .map_err(|e| PageError::ParseError(format!("invalid canonical url {canonical}: {e}")))?;
+
+        Ok(())
+    }
+
     /// Get the title from a body of HTML
-    fn get_title_from_body(body: &String) -> Result<String, ReqwestError> {
-        println!("{body:?}");
+    fn get_title_from_body(body: &str) -> Option<String> {
+        html::page_title(body)
+    }
+
+    /// Get the first sentence of the lead paragraph of the page as plain text, loading the body as well if necessary
+    ///
+    /// Full stops after common abbreviations and initials (like in "Dr." or "U.S.") aren't treated as the end of
+    /// the sentence. Returns an empty string if the page has no lead paragraph
+    pub fn get_first_sentence(&mut self) -> Result<String, PageError> {
+        let lead = Self::get_lead_from_body(self.get_body()?).unwrap_or_default();
 
-        let title_unparsed = body
-            .lines()
-            .filter(|l| l.contains("<title>"))
-            .collect::<String>();
+        Ok(first_sentence(&lead).to_string())
+    }
 
-        println!("{:?}", &title_unparsed);
+    /// Get the lead paragraph of the page as plain text, loading the body as well if necessary
+    ///
+    /// This is the first non-empty paragraph of the article content with the markup and reference markers like
+    /// `[1]` stripped, see [Page::get_first_sentence] for only its first sentence. Returns an empty string if the
+    /// page has no lead paragraph
+    pub fn get_summary(&mut self) -> Result<String, PageError> {
+        Ok(Self::get_lead_from_body(self.get_body()?).unwrap_or_default())
+    }
 
-        let regex = Regex::new(r"([a-zA-Z ]+) - Wikipedia").expect("Title regex failed to compile");
+    /// Get the readable prose of the article as plain text, one paragraph per line, loading the body as well if
+    /// necessary
+    ///
+    /// Only the paragraphs of the article content are included, so headings, lists, tables, infoboxes and
+    /// navigation boxes are left out. Reference markers like `[1]`, maintenance notes like `[citation needed]`,
+    /// edit links and embedded styles and scripts are removed
+    pub fn get_article_text(&mut self) -> Result<String, PageError> {
+        Ok(Self::get_article_text_from_body(self.get_body()?))
+    }
 
-        match regex.captures(&title_unparsed) {
-            Some(captures) => Ok(captures.extract::<1>().1[0].to_string()),
-            None => panic!("Failed to find title of wikipedia page"),
-        }
+    /// Count the words of the prose of the article, see [Page::get_article_text]
+    pub fn get_word_count(&mut self) -> Result<usize, PageError> {
+        Ok(self.get_article_text()?.split_whitespace().count())
+    }
+
+    /// Get the paragraphs of the article content from a body of HTML as plain text, see [Page::get_article_text]
+    fn get_article_text_from_body(body: &str) -> String {
+        let content = Self::get_prose_html_from_body(body);
+
+        PARAGRAPH_REGEX
+            .captures_iter(&content)
+            .map(|c| html_to_text(&c[1]))
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Get the HTML of the article content from a body of HTML, with the markup that isn't part of the prose (see
+    /// [NON_PROSE_REGEX]) removed
+    fn get_prose_html_from_body(body: &str) -> Cow<'_, str> {
+        let content = &body[body.find("id=\"mw-content-text\"").unwrap_or(0)..];
+        let end = CONTENT_END_MARKERS
+            .iter()
+            .filter_map(|marker| content.find(marker))
+            .min()
+            .unwrap_or(content.len());
+
+        NON_PROSE_REGEX.replace_all(&content[..end], "")
+    }
+
+    /// Get the first non-empty paragraph of the article content from a body of HTML as plain text
+    fn get_lead_from_body(body: &str) -> Option<String> {
+        let content = Self::get_prose_html_from_body(body);
+
+        PARAGRAPH_REGEX
+            .captures_iter(&content)
+            .map(|c| html_to_text(&c[1]))
+            .find(|paragraph| !paragraph.is_empty())
     }
 
     /// Create a new [Page] and immediatly load the title
-    pub fn new_load_title(wiki_url: WikipediaUrl) -> Result<Self, ReqwestError> {
+    pub fn new_load_title(wiki_url: WikipediaUrl) -> Result<Self, PageError> {
         let mut page = Page::new(wiki_url);
 
         page.load_title()?;
@@ -236,64 +895,1239 @@ impl Page {
             title: Some(title),
             url: wiki_url,
             body: None,
+            fetcher: None,
+            source: None,
         }
     }
 
     /// Get a list of [Page]s for all of the Wikipedia links on the page, loading the body as well if necessary
-    pub fn get_connections(&mut self) -> Result<Vec<Page>, ReqwestError> {
-        Self::get_connections_from_body(self.get_body()?)
+    ///
+    /// Only links to articles are included, links to other namespaces (see [Namespace]) like `Category:` or `File:`
+    /// pages are left out. Each linked page is only listed once, in the order it's first linked
+    pub fn get_connections(&mut self) -> Result<Vec<Page>, PageError> {
+        let lang = self.url.lang().to_string();
+        let connections = Self::get_connections_from_body(self.get_body()?, &lang);
+
+        Ok(self.inherit_wiki(connections))
     }
 
     /// Only get a list of [Page]s for all of the Wikipedia links on the page if the body is already loaded
-    pub fn try_get_connections(&self) -> Option<Result<Vec<Page>, ReqwestError>> {
-        match &self.body {
-            Some(body) => Some(Self::get_connections_from_body(body)),
-            None => None,
-        }
-    }
-
-    /// Get a list of [Page]s for all of the Wikipedia links on the page from a body of HTML
-    fn get_connections_from_body(body: &String) -> Result<Vec<Page>, ReqwestError> {
-        let wiki_regex = Regex::new(
-            "<a href=\"(/wiki/[a-zA-Z_\\(\\)]+)\"(?: class=\"[a-zA-Z-_]\")? title=\"([a-zA-Z ]+)\"",
-        )
-        .unwrap();
-
-        let captures = wiki_regex
-            .captures_iter(&body)
-            .map(|c| c.extract::<2>())
-            .filter(|c| !c.0.contains("Wayback Machine"))
-            //.map(|c| dbg!(c))
-            .filter_map(|c| {
-                Some(Page::new_with_title(
-                    WikipediaUrl::from_path(c.1[0].to_string()).ok()?,
-                    c.1[1].to_string(),
-                ))
+    pub fn try_get_connections(&self) -> Option<Result<Vec<Page>, PageError>> {
+        self.body.as_ref().map(|body| {
+            Ok(self.inherit_wiki(Self::get_connections_from_body(body, self.url.lang())))
+        })
+    }
+
+    /// Get a list of [Page]s for all of the Wikipedia links on the page, including links outside of the article
+    /// namespace such as categories, files and templates, loading the body as well if necessary
+    ///
+    /// Like [Page::get_connections] each linked page is only listed once
+    pub fn get_connections_including_namespaces(&mut self) -> Result<Vec<Page>, PageError> {
+        let connections = dedup_by_path(Self::links_in(self.get_body()?)).collect();
+
+        Ok(self.inherit_wiki(connections))
+    }
+
+    /// Get a list of [Page]s for the first `max` Wikipedia links on the page, loading the body as well if necessary
+    ///
+    /// Like [Page::get_connections], but parsing stops once `max` distinct pages have been found, so the rest of the
+    /// body is never scanned. This returns the same pages as [Page::get_connections] when the page has at most
+    /// `max` connections
+    pub fn get_connections_limited(&mut self, max: usize) -> Result<Vec<Page>, PageError> {
+        let lang = self.url.lang().to_string();
+        let connections = dedup_by_path(Self::connections_in(self.get_body()?, &lang)).take(max).collect();
+
+        Ok(self.inherit_wiki(connections))
+    }
+
+    /// Get a list of [Page]s for all of the Wikipedia links on the page from a body of HTML from the Wikipedia in
+    /// `lang`
+    fn get_connections_from_body(body: &str, lang: &str) -> Vec<Page> {
+        dedup_by_path(Self::connections_in(body, lang)).collect()
+    }
+
+    /// Iterate over the [Page]s for all of the Wikipedia article links in a fragment of HTML from the Wikipedia in
+    /// `lang`, which decides the local [Namespace] prefixes that are left out
+    fn connections_in<'a>(html: &'a str, lang: &'a str) -> impl Iterator<Item = Page> + 'a {
+        Self::links_in(html)
+            .filter(move |page| Namespace::from_title_with_lang(&page.url.title_from_url(), lang) == Namespace::Article)
+    }
+
+    /// Iterate over the [Page]s for the Wikipedia links in a fragment of HTML, in any [Namespace]
+    fn links_in(html: &str) -> impl Iterator<Item = Page> + '_ {
+        html::wiki_links(html)
+            .filter(|(_, title)| title != "Wayback Machine")
+            .filter_map(|(path, title)| Some(Page::new_with_title(WikipediaUrl::from_path(path).ok()?, title)))
+    }
+
+    /// Get the [Page]s for all of the Wikipedia links on the page grouped by their [Namespace], loading the body as well if necessary
+    ///
+    /// Unlike [Page::get_connections] this includes links outside of the article namespace, such as categories and files
+    pub fn get_connections_by_namespace(&mut self) -> Result<HashMap<Namespace, Vec<Page>>, PageError> {
+        let mut connections: HashMap<Namespace, Vec<Page>> = HashMap::new();
+
+        let pages = Self::links_in(self.get_body()?).collect();
+
+        for page in self.inherit_wiki(pages) {
+            connections.entry(page.url.namespace()).or_default().push(page);
+        }
+
+        Ok(connections)
+    }
+
+    /// Get the absolute `http` and `https` urls the page links to outside of Wikipedia, like citation sources,
+    /// loading the body as well if necessary
+    ///
+    /// Links to any `wikipedia.org` host are left out, and each url is only listed once, in the order it's first
+    /// linked
+    pub fn get_external_links(&mut self) -> Result<Vec<Url>, PageError> {
+        let mut seen = HashSet::new();
+
+        Ok(html::absolute_links(self.get_body()?)
+            .filter_map(|href| Url::parse(&href).ok())
+            .filter(|url| {
+                url.host_str()
+                    .is_some_and(|host| host != "wikipedia.org" && !host.ends_with(".wikipedia.org"))
             })
-            //.map(|c| dbg!(c))
-            .collect::<Vec<Page>>();
+            .filter(|url| seen.insert(url.clone()))
+            .collect())
+    }
 
-        Ok(captures)
+    /// Get the url of the main image of the page, loading the body as well if necessary
+    ///
+    /// This is the image Wikipedia picks for link previews (the `og:image` meta tag), usually the infobox image.
+    /// Returns `None` if the page has no image
+    pub fn get_thumbnail(&mut self) -> Result<Option<Url>, PageError> {
+        let Some(image) = html::meta_property(self.get_body()?, "og:image") else {
+            return Ok(None);
+        };
+
+        self.get_url()
+            .join(&image)
+            .map(Some)
+            .map_err(|e| PageError::ParseError(format!("invalid image url {image}: {e}")))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::{Page, WikipediaUrl};
+    /// Get the names of the categories the page is in, like `Belgian cuisine`, loading the body as well if
+    /// necessary
+    ///
+    /// These are the categories listed in the footer of the article. Hidden maintenance categories like
+    /// `Articles with short description` are left out, see [Page::get_hidden_categories]
+    pub fn get_categories(&mut self) -> Result<Vec<String>, PageError> {
+        Ok(Self::categories_in(self.get_body()?, NORMAL_CATEGORIES_ID))
+    }
 
-    #[test]
-    fn test_get_connections() {
-        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
-        let mut waffle_page = Page::new(url);
+    /// Get the names of the hidden categories the page is in, which are used to track maintenance of articles
+    /// rather than their subject, loading the body as well if necessary
+    pub fn get_hidden_categories(&mut self) -> Result<Vec<String>, PageError> {
+        Ok(Self::categories_in(self.get_body()?, HIDDEN_CATEGORIES_ID))
+    }
 
-        waffle_page.get_connections().unwrap();
+    /// Get the names of the categories listed in the category footer block with the id attribute `id`
+    fn categories_in(body: &str, id: &str) -> Vec<String> {
+        let Some(start) = body.find(id) else {
+            return Vec::new();
+        };
+
+        // The list follows a link to the help page about categories in the same block
+        let block = &body[start..];
+        let block = &block[..block.find("</div>").unwrap_or(block.len())];
+        let list = &block[block.find("<ul").unwrap_or(block.len())..];
+
+        html::wiki_links(list)
+            .filter_map(|(_, title)| Some(title.split_once(':')?.1.to_string()))
+            .collect()
     }
 
-    #[test]
-    fn test_page_initialization() {
-        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
-        let waffle_page = Page::new_load_title(url).unwrap();
+    /// Get a list of [Page]s for the Wikipedia links in the "See also" section of the page, loading the body as well if necessary
+    ///
+    /// Returns an empty list if the page has no "See also" section
+    pub fn get_see_also(&mut self) -> Result<Vec<Page>, PageError> {
+        let lang = self.url.lang().to_string();
+        let connections = Self::get_see_also_from_body(self.get_body()?, &lang);
 
-        assert_eq!(waffle_page.title.unwrap().as_str(), "Waffle")
+        Ok(self.inherit_wiki(connections))
+    }
+
+    /// Get a list of [Page]s for the Wikipedia links in the "See also" section of a body of HTML
+    fn get_see_also_from_body(body: &str, lang: &str) -> Vec<Page> {
+        let Some(start) = body.find(SEE_ALSO_HEADING_ID) else {
+            return Vec::new();
+        };
+
+        let section = &body[start + SEE_ALSO_HEADING_ID.len()..];
+        let section = &section[..section.find("<h2").unwrap_or(section.len())];
+
+        Self::connections_in(section, lang).collect()
+    }
+
+    /// Get a list of [Page]s for the Wikipedia links in the hatnotes of the page, loading the body as well if necessary
+    ///
+    /// Hatnotes are the "For other uses, see ..." and "Not to be confused with ..." notes at the top of an article
+    /// or section, which mostly point to disambiguation pages and similarly named articles
+    pub fn get_hatnote_targets(&mut self) -> Result<Vec<Page>, PageError> {
+        let body = self.get_body()?;
+
+        let targets = HATNOTE_REGEX
+            .captures_iter(body)
+            .flat_map(|hatnote| {
+                Self::links_in(hatnote.get(1).expect("Hatnote regex has a group").as_str()).collect::<Vec<_>>()
+            })
+            .collect();
+
+        Ok(self.inherit_wiki(targets))
+    }
+
+    /// Check if the page is a disambiguation page, like `Mercury (disambiguation)`, loading the body as well if
+    /// necessary
+    ///
+    /// Disambiguation pages list unrelated articles sharing a name, so a crawler usually shouldn't expand them. They
+    /// are recognized by their disambiguation box or category, or by a lead paragraph ending in "may refer to:"
+    pub fn is_disambiguation(&mut self) -> Result<bool, PageError> {
+        let body = self.get_body()?;
+
+        if DISAMBIGUATION_MARKERS.iter().any(|marker| body.contains(marker)) {
+            return Ok(true);
+        }
+
+        let lead = Self::get_lead_from_body(body).unwrap_or_default();
+
+        Ok(lead.ends_with("may refer to:") || lead.ends_with("may also refer to:"))
+    }
+
+    /// Get up to `max` [Page]s for the first Wikipedia links on the page without downloading the full body
+    ///
+    /// This is a best-effort partial fetch: the body is streamed in chunks and reading stops once `max` links
+    /// have been found or the end of the article content (`mw-content-text`) is reached. The streamed body isn't
+    /// stored in the struct, and a read error part way through the stream ends it early rather than failing.
+    /// If the body is already loaded it's used instead of fetching the page again
+    pub fn get_connections_partial(&mut self, max: usize) -> Result<Vec<Page>, PageError> {
+        if self.body.is_some() {
+            return self.get_connections_limited(max);
+        }
+
+        let mut stream = self.stream_connections()?;
+        stream.stop_at_content_end = true;
+
+        Ok(stream.take(max).map_while(Result::ok).collect())
+    }
+
+    /// Stream the [Page]s for all of the Wikipedia links on the page as the body is downloaded
+    ///
+    /// The body is read and parsed in chunks and never stored, so memory use stays bounded on huge pages. This
+    /// always fetches the page, even if the body is already loaded. Like [Page::get_connections] each linked page is
+    /// only listed once. A page given a [BodySource] (see [Page::with_source]) loads the whole body from it before
+    /// streaming it
+    pub fn stream_connections(&self) -> Result<ConnectionStream, PageError> {
+        let reader: Box<dyn Read + Send> = match &self.source {
+            Some(source) => Box::new(io::Cursor::new(source.fetch(self.get_url())?)),
+            None => Box::new(self.fetcher().get_streamed(self.get_url().clone())?),
+        };
+
+        Ok(ConnectionStream::new(BufReader::with_capacity(STREAM_CHUNK_LEN, reader), self.without_body()))
+    }
+
+    /// Count the unique Wikipedia pages reachable from `start` within `depth` hops, including `start` itself
+    ///
+    /// This does a breadth-first search using only link extraction, so no graph is built and bodies are dropped
+    /// as soon as their connections are read. Every page in the frontier is still fetched, so the number of
+    /// requests grows quickly with `depth`. Pages are told apart by their normalized url (see
+    /// [WikipediaUrl::normalized]), and none is fetched twice
+    ///
+    /// The first page that fails to load aborts the whole count with its error, there's no partial result
+    pub fn reachable_count(start: &Page, depth: usize) -> Result<usize, PageError> {
+        let mut visited: HashSet<Url> = HashSet::from([start.url.normalized().0]);
+        let mut frontier: Vec<Page> = vec![start.clone()];
+
+        for _ in 0..depth {
+            let mut next_frontier = Vec::new();
+
+            for mut page in frontier {
+                for connection in page.get_connections()? {
+                    if visited.insert(connection.url.normalized().0) {
+                        next_frontier.push(connection);
+                    }
+                }
+            }
+
+            if next_frontier.is_empty() {
+                break;
+            }
+
+            frontier = next_frontier;
+        }
+
+        Ok(visited.len())
+    }
+}
+
+/// An iterator over the [Page]s for the Wikipedia links on a page, parsed as the body is downloaded,
+/// see [Page::stream_connections]
+///
+/// The body is scanned a chunk at a time, and the end of a chunk that stops part way through a tag, comment or
+/// `<script>` element is carried over to the next one, so the links found are the same as [Page::get_connections]
+/// would find. Only a single construct longer than a chunk, like a large inline script, is buffered whole
+pub struct ConnectionStream {
+    /// The body being read, dropped once the stream is done to free its connection slot (see
+    /// [FetchConfig::max_connections_per_host](fetch::FetchConfig::max_connections_per_host))
+    reader: Option<BufReader<Box<dyn Read + Send>>>,
+    /// The bytes read but not scanned yet
+    carried: Vec<u8>,
+    pending: VecDeque<Page>,
+    seen: HashSet<String>,
+    source: Page,
+    stop_at_content_end: bool,
+    done: bool,
+}
+
+impl ConnectionStream {
+    fn new(reader: BufReader<Box<dyn Read + Send>>, source: Page) -> Self {
+        ConnectionStream {
+            reader: Some(reader),
+            carried: Vec::new(),
+            pending: VecDeque::new(),
+            seen: HashSet::new(),
+            source,
+            stop_at_content_end: false,
+            done: false,
+        }
+    }
+
+    /// Scan the complete part of the carried bytes for links, keeping the rest for the next chunk unless `end` is
+    /// set because the body has been read
+    fn scan_carried(&mut self, end: bool) {
+        // Leave a character split between chunks for the next one
+        let utf8_len = match std::str::from_utf8(&self.carried) {
+            Err(e) if !end && e.error_len().is_none() => e.valid_up_to(),
+            _ => self.carried.len(),
+        };
+
+        let text = String::from_utf8_lossy(&self.carried[..utf8_len]).into_owned();
+        let mut scan_len = if end { text.len() } else { html::complete_len(&text) };
+
+        if self.stop_at_content_end
+            && let Some(marker) = CONTENT_END_MARKERS.iter().filter_map(|marker| text[..scan_len].find(marker)).min()
+        {
+            scan_len = marker;
+            self.done = true;
+        }
+
+        let seen = &mut self.seen;
+
+        self.pending.extend(
+            Page::connections_in(&text[..scan_len], self.source.url.lang()).filter(|page| seen.insert(page.get_url().path().to_string())),
+        );
+
+        let mut rest = text.as_bytes()[scan_len..].to_vec();
+        rest.extend_from_slice(&self.carried[utf8_len..]);
+
+        self.carried = rest;
+    }
+}
+
+impl Iterator for ConnectionStream {
+    type Item = Result<Page, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(mut page) = self.pending.pop_front() {
+                self.source.adopt(&mut page);
+
+                return Some(Ok(page));
+            }
+
+            if self.done {
+                self.reader = None;
+
+                return None;
+            }
+
+            let reader = self.reader.as_mut()?;
+
+            match reader.fill_buf() {
+                Ok([]) => {
+                    self.scan_carried(true);
+                    self.done = true;
+                }
+                Ok(chunk) => {
+                    let len = chunk.len();
+
+                    self.carried.extend_from_slice(chunk);
+                    reader.consume(len);
+
+                    self.scan_carried(false);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.done = true;
+                    self.reader = None;
+
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Keep the first [Page] for each url path, in order
+fn dedup_by_path(pages: impl Iterator<Item = Page>) -> impl Iterator<Item = Page> {
+    let mut seen = HashSet::new();
+
+    pages.filter(move |page| seen.insert(page.get_url().path().to_string()))
+}
+
+/// Strip the tags and reference markers out of a fragment of HTML, decoding entities and collapsing whitespace
+fn html_to_text(html: &str) -> String {
+    // Decode first, since Wikipedia writes the brackets of reference markers as `&#91;` and `&#93;`
+    let text = html::decode_entities(&TAG_REGEX.replace_all(html, ""));
+    let text = REFERENCE_REGEX.replace_all(&text, "");
+
+    text.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Get the text up to and including the first sentence boundary
+///
+/// A boundary is a `.`, `!` or `?` followed by whitespace and an uppercase letter or digit, unless the `.` ends
+/// an abbreviation, a single letter initial or a dotted acronym like `U.S.`
+fn first_sentence(text: &str) -> &str {
+    for (i, c) in text.char_indices() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+
+        let end = i + c.len_utf8();
+        let rest = &text[end..];
+
+        if rest.is_empty() {
+            return text;
+        }
+
+        let next_starts_sentence = rest.starts_with(char::is_whitespace)
+            && rest
+                .trim_start()
+                .starts_with(|next: char| next.is_uppercase() || next.is_ascii_digit());
+
+        if !next_starts_sentence {
+            continue;
+        }
+
+        if c == '.' {
+            let word = text[..i].rsplit(char::is_whitespace).next().unwrap_or("");
+            let word = word.trim_start_matches(['(', '"', '\'']);
+
+            let is_initial = word.chars().count() == 1 && word.chars().all(char::is_alphabetic);
+
+            if is_initial || word.contains('.') || ABBREVIATIONS.contains(&word) {
+                continue;
+            }
+        }
+
+        return &text[..end];
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::RandomState;
+    use std::collections::{HashMap, HashSet};
+    use std::hash::BuildHasher;
+    use std::io::{BufReader, Cursor, Read};
+    use std::sync::{Arc, Mutex};
+
+    use reqwest::Url;
+
+    use crate::fetch::{BodySource, SourceError};
+    use crate::{ConnectionStream, Namespace, Page, PageError, WikipediaUrl, WikipediaUrlInvalidError, html_to_text};
+
+    /// A [BodySource] answering requests with canned bodies, recording the urls it was asked for
+    #[derive(Debug)]
+    pub(crate) struct MockSource {
+        body: String,
+        bodies: HashMap<String, String>,
+        fetched: Mutex<Vec<Url>>,
+    }
+
+    impl MockSource {
+        /// Answer every request with `body`
+        pub(crate) fn new(body: &str) -> Arc<Self> {
+            Self::with_bodies(body, &[])
+        }
+
+        /// Answer requests for the url paths in `bodies` with their body, and every other request with `body`
+        pub(crate) fn with_bodies(body: &str, bodies: &[(&str, &str)]) -> Arc<Self> {
+            Arc::new(MockSource {
+                body: body.to_string(),
+                bodies: bodies
+                    .iter()
+                    .map(|(path, body)| (path.to_string(), body.to_string()))
+                    .collect(),
+                fetched: Mutex::new(Vec::new()),
+            })
+        }
+
+        /// Get the urls fetched so far, in order
+        pub(crate) fn fetched(&self) -> Vec<Url> {
+            self.fetched.lock().unwrap().clone()
+        }
+    }
+
+    impl BodySource for MockSource {
+        fn fetch(&self, url: &Url) -> Result<String, SourceError> {
+            self.fetched.lock().unwrap().push(url.clone());
+
+            Ok(self.bodies.get(url.path()).unwrap_or(&self.body).clone())
+        }
+    }
+
+    #[test]
+    fn test_get_connections() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.get_connections().unwrap();
+    }
+
+    #[test]
+    fn test_page_initialization() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let waffle_page = Page::new_load_title(url).unwrap();
+
+        assert_eq!(waffle_page.title.unwrap().as_str(), "Waffle")
+    }
+
+    #[test]
+    fn test_resolve_redirect() {
+        let url = WikipediaUrl::from_path("/wiki/USA").unwrap();
+        let mut usa_page = Page::new(url);
+
+        usa_page.resolve_redirect().unwrap();
+
+        assert_eq!(usa_page.get_url().path(), "/wiki/United_States");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_page() {
+        let mut waffle_page = Page::new_with_title(WikipediaUrl::from_path("/wiki/Waffle").unwrap(), "Waffle".to_string());
+        waffle_page.body = Some("<p>A waffle</p>".to_string());
+
+        let json = serde_json::to_string(&waffle_page).unwrap();
+        assert_eq!(json, r#"{"title":"Waffle","url":"https://en.wikipedia.org/wiki/Waffle"}"#);
+
+        let deserialized: Page = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, waffle_page);
+        assert_eq!(deserialized.title.as_deref(), Some("Waffle"));
+        assert!(deserialized.body.is_none());
+
+        assert!(serde_json::from_str::<WikipediaUrl>(r#""https://evil.com/wiki/Waffle""#).is_err());
+    }
+
+    #[test]
+    fn test_mock_source() {
+        let source = MockSource::new(concat!(
+            "<title>Waffle - Wikipedia</title>",
+            "<a href=\"/wiki/Batter_(cooking)\" title=\"Batter (cooking)\">batter</a>",
+            "<a href=\"/wiki/Belgium\" title=\"Belgium\">Belgium</a>",
+        ));
+
+        let url = WikipediaUrl::from_path("/wiki/Waffle").unwrap();
+        let mut waffle_page = Page::with_source(url, source.clone());
+
+        assert_eq!(waffle_page.get_title().unwrap(), "Waffle");
+        assert_eq!(
+            waffle_page
+                .get_connections()
+                .unwrap()
+                .iter()
+                .map(|page| page.get_url().path())
+                .collect::<Vec<_>>(),
+            vec!["/wiki/Batter_(cooking)", "/wiki/Belgium"]
+        );
+        assert_eq!(source.fetched(), vec![waffle_page.get_url().clone()]);
+
+        let mut belgium_page = waffle_page.stream_connections().unwrap().nth(1).unwrap().unwrap();
+        belgium_page.load_body().unwrap();
+
+        assert_eq!(source.fetched().len(), 3);
+        assert_eq!(source.fetched()[2].path(), "/wiki/Belgium");
+    }
+
+    #[test]
+    fn test_url_lang() {
+        let url = WikipediaUrl::from_path_with_lang("/wiki/Waffel", "de").unwrap();
+        assert_eq!(url.get_url().as_str(), "https://de.wikipedia.org/wiki/Waffel");
+        assert_eq!(url.lang(), "de");
+
+        assert_eq!(WikipediaUrl::from_path("/wiki/Waffle").unwrap().lang(), "en");
+        assert_eq!(WikipediaUrl::new_any_lang("https://fr.wikipedia.org/wiki/Gaufre").unwrap().lang(), "fr");
+
+        assert!(WikipediaUrl::new("https://de.wikipedia.org/wiki/Waffel").is_err());
+        assert!(WikipediaUrl::new_any_lang("https://evil.com/wiki/Waffle").is_err());
+        assert!(WikipediaUrl::new_any_lang("https://en.wikipedia.org.evil.com/wiki/Waffle").is_err());
+        assert!(WikipediaUrl::new_any_lang("https://wiki.wikipedia.org/wiki/Waffle").is_err());
+
+        let mut waffel_page = Page::new(url);
+        waffel_page.body = Some("<a href=\"/wiki/Teig\" title=\"Teig\">Teig</a>".to_string());
+
+        let connections = waffel_page.get_connections().unwrap();
+        assert_eq!(connections[0].get_url().as_str(), "https://de.wikipedia.org/wiki/Teig");
+    }
+
+    #[test]
+    fn test_url_from_str() {
+        let path: WikipediaUrl = "/wiki/Waffle".parse().unwrap();
+        assert_eq!(path.to_string(), "https://en.wikipedia.org/wiki/Waffle");
+
+        let full: WikipediaUrl = "https://de.wikipedia.org/wiki/Waffel".parse().unwrap();
+        assert_eq!(full.to_string(), "https://de.wikipedia.org/wiki/Waffel");
+        assert_eq!(WikipediaUrl::try_from("https://de.wikipedia.org/wiki/Waffel").unwrap(), full);
+
+        assert!(matches!(
+            "https://evil.com/wiki/Waffle".parse::<WikipediaUrl>(),
+            Err(WikipediaUrlInvalidError::InvalidHostError(_))
+        ));
+    }
+
+    #[test]
+    fn test_title_from_url() {
+        let title = |path: &str| WikipediaUrl::from_path(path).unwrap().title_from_url();
+
+        assert_eq!(title("/wiki/Cura%C3%A7ao"), "Curaçao");
+        assert_eq!(title("/wiki/New_York"), "New York");
+    }
+
+    #[test]
+    fn test_normalized_url() {
+        let normalized = WikipediaUrl::from_path("/wiki/cura%C3%A7ao_island#History")
+            .unwrap()
+            .normalized();
+
+        assert_eq!(
+            normalized.get_url().as_str(),
+            WikipediaUrl::from_path("/wiki/Curaçao island").unwrap().normalized().get_url().as_str()
+        );
+        assert_eq!(normalized.get_url().path(), "/wiki/Cura%C3%A7ao_island");
+    }
+
+    #[test]
+    fn test_url_from_title() {
+        let url = WikipediaUrl::from_title("belgian waffle").unwrap();
+        assert_eq!(url.get_url().path(), "/wiki/Belgian_waffle");
+
+        let url = WikipediaUrl::from_title("What? #1").unwrap();
+        assert_eq!(url.get_url().query(), None);
+        assert_eq!(url.title_from_url(), "What? #1");
+    }
+
+    #[test]
+    fn test_parse_punctuated_titles() {
+        let url = WikipediaUrl::from_path("/wiki/Bj%C3%B8rn_Borg".to_string()).unwrap();
+        let mut borg_page = Page::new(url);
+
+        borg_page.body = Some(
+            concat!(
+                "<!DOCTYPE html>\n<html><head>\n<title>Bjørn Borg - Wikipedia</title>\n</head><body>",
+                "<p><a href=\"/wiki/Guns_N%27_Roses\" title=\"Guns N&#39; Roses\">Guns N' Roses</a>, ",
+                "<a href=\"/wiki/20th_century\" class=\"mw-redirect\" title=\"20th century\">20th century</a> and ",
+                "<a href=\"/wiki/Paris,_Texas\" title=\"Paris, Texas\">Paris, Texas</a></p>",
+                "<a href=\"/wiki/Category:Swedish_tennis_players\" title=\"Category:Swedish tennis players\">",
+                "</body></html>",
+            )
+            .to_string(),
+        );
+
+        assert_eq!(borg_page.get_title().unwrap(), "Bjørn Borg");
+
+        let connections = borg_page.get_connections().unwrap();
+
+        assert_eq!(
+            connections.iter().map(|page| page.title.as_deref().unwrap()).collect::<Vec<_>>(),
+            vec!["Guns N' Roses", "20th century", "Paris, Texas"]
+        );
+        assert_eq!(connections[0].get_url().path(), "/wiki/Guns_N%27_Roses");
+    }
+
+    #[test]
+    fn test_parse_non_latin_titles() {
+        let url = WikipediaUrl::from_path_with_lang("/wiki/%E3%83%AF%E3%83%83%E3%83%95%E3%83%AB", "ja").unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some(
+            concat!(
+                "<title>ワッフル - Wikipedia</title>",
+                "<a href=\"/wiki/%E6%9D%B1%E4%BA%AC%E9%83%BD\" title=\"東京都\">東京</a>",
+                "<a href=\"/wiki/Spider-Man_2\" title=\"Spider-Man 2\">Spider-Man 2</a>",
+                "<a href=\"/wiki/%CE%91%CE%B8%CE%AE%CE%BD%CE%B1\" title=\"Αθήνα\">Αθήνα</a>",
+                "<a href=\"/wiki/%D0%9C%D0%BE%D1%81%D0%BA%D0%B2%D0%B0\" title=\"Москва\">Москва</a>",
+                "<a href=\"/wiki/%D9%82%D8%A7%D9%87%D8%B1%D8%A9\" title=\"قاهرة\">قاهرة</a>",
+                "<a href=\"/wiki/St._John%27s,_Newfoundland_(city)\" title=\"St. John's, Newfoundland (city)\">",
+            )
+            .to_string(),
+        );
+
+        assert_eq!(waffle_page.get_title().unwrap(), "ワッフル");
+
+        let connections = waffle_page.get_connections().unwrap();
+
+        assert_eq!(
+            connections.iter().map(|page| page.title.as_deref().unwrap()).collect::<Vec<_>>(),
+            vec!["東京都", "Spider-Man 2", "Αθήνα", "Москва", "قاهرة", "St. John's, Newfoundland (city)"]
+        );
+        assert_eq!(connections[0].get_url().as_str(), "https://ja.wikipedia.org/wiki/%E6%9D%B1%E4%BA%AC%E9%83%BD");
+        assert_eq!(connections[0].url.title_from_url(), "東京都");
+    }
+
+    #[test]
+    fn test_connections_deduplicated() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some(
+            concat!(
+                "<a href=\"/wiki/Belgium\" title=\"Belgium\">Belgium</a>",
+                "<a href=\"/wiki/Belgium\" title=\"Belgium\">Belgian</a>",
+                "<a href=\"/wiki/Belgium#History\" title=\"Belgium\">history</a>",
+            )
+            .to_string(),
+        );
+
+        let connections = waffle_page.get_connections().unwrap();
+
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].get_url().path(), "/wiki/Belgium");
+    }
+
+    #[test]
+    fn test_get_connections_limited() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some(
+            concat!(
+                "<a href=\"/wiki/Belgium\" title=\"Belgium\">Belgium</a>",
+                "<a href=\"/wiki/Belgium\" title=\"Belgium\">Belgian</a>",
+                "<a href=\"/wiki/Category:Waffles\" title=\"Category:Waffles\">Waffles</a>",
+                "<a href=\"/wiki/Batter_(cooking)\" title=\"Batter (cooking)\">batter</a>",
+                "<a href=\"/wiki/Stroopwafel\" title=\"Stroopwafel\">stroopwafel</a>",
+            )
+            .to_string(),
+        );
+
+        let limited = waffle_page.get_connections_limited(2).unwrap();
+
+        assert_eq!(limited.len(), 2);
+        assert_eq!(limited[0].get_url().path(), "/wiki/Belgium");
+        assert_eq!(limited[1].get_url().path(), "/wiki/Batter_(cooking)");
+
+        assert_eq!(waffle_page.get_connections_limited(10).unwrap(), waffle_page.get_connections().unwrap());
+    }
+
+    #[test]
+    fn test_connections_skip_namespaces() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some(
+            concat!(
+                "<a href=\"/wiki/Category:Waffles\" title=\"Category:Waffles\">Waffles</a>",
+                "<a href=\"/wiki/File:Waffle.jpg\" class=\"mw-file-description\" title=\"File:Waffle.jpg\">",
+                "<a href=\"/wiki/Batter_(cooking)\" title=\"Batter (cooking)\">batter</a>",
+            )
+            .to_string(),
+        );
+
+        let connections = waffle_page.get_connections().unwrap();
+
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].get_url().path(), "/wiki/Batter_(cooking)");
+
+        let connections = waffle_page.get_connections_including_namespaces().unwrap();
+
+        assert_eq!(connections.len(), 3);
+        assert_eq!(connections[0].get_url().path(), "/wiki/Category:Waffles");
+        assert_eq!(connections[1].get_url().path(), "/wiki/File:Waffle.jpg");
+    }
+
+    #[test]
+    fn test_page_equality_ignores_loaded_state() {
+        let unloaded = Page::new(WikipediaUrl::from_path("/wiki/Waffle").unwrap());
+
+        let mut loaded = Page::new_with_title(WikipediaUrl::from_path("/wiki/Waffle").unwrap(), "Waffle".to_string());
+        loaded.body = Some("<title>Waffle - Wikipedia</title>".to_string());
+
+        let hasher = RandomState::new();
+
+        assert_eq!(unloaded, loaded);
+        assert_eq!(hasher.hash_one(&unloaded), hasher.hash_one(&loaded));
+        assert_ne!(unloaded, Page::new(WikipediaUrl::from_path("/wiki/Belgium").unwrap()));
+
+        // Only the url is hashed, so the interior mutability of the fetcher can't change a page's hash
+        #[allow(clippy::mutable_key_type)]
+        let visited = HashSet::from([loaded]);
+        assert!(visited.contains(&unloaded));
+    }
+
+    #[test]
+    fn test_title_not_found() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some("<p>A stub without a head</p>".to_string());
+
+        assert!(matches!(waffle_page.get_title(), Err(PageError::TitleNotFoundError(_))));
+        assert!(matches!(waffle_page.try_get_title(), Err(PageError::TitleNotFoundError(_))));
+    }
+
+    #[test]
+    fn test_get_connections_by_namespace() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some(
+            concat!(
+                "<a href=\"/wiki/Batter\" title=\"Batter\">batter</a>",
+                "<a href=\"/wiki/Category:Belgian_cuisine\" title=\"Category:Belgian cuisine\">Belgian cuisine</a>",
+                "<a href=\"/wiki/File:Waffles\" title=\"File:Waffles\">image</a>",
+                "<a href=\"/wiki/User_talk:Waffle\" title=\"User talk:Waffle\">talk</a>",
+            )
+            .to_string(),
+        );
+
+        let connections = waffle_page.get_connections_by_namespace().unwrap();
+
+        assert_eq!(connections[&Namespace::Article].len(), 1);
+        assert_eq!(connections[&Namespace::Category][0].get_url().path(), "/wiki/Category:Belgian_cuisine");
+        assert_eq!(connections[&Namespace::File].len(), 1);
+        assert_eq!(connections[&Namespace::Talk].len(), 1);
+        assert_eq!(Namespace::from_title("Star Wars: Episode IV"), Namespace::Article);
+        assert_eq!(Namespace::from_title("Media:Waffles.jpg"), Namespace::Media);
+        assert_eq!(Namespace::from_title("TimedText:Waffles.webm.en.srt"), Namespace::TimedText);
+    }
+
+    #[test]
+    fn test_get_connections_de() {
+        let url = WikipediaUrl::from_path_with_lang("/wiki/Waffel", "de").unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some(
+            concat!(
+                "<a href=\"/wiki/Teig\" title=\"Teig\">Teig</a>",
+                "<a href=\"/wiki/Kategorie:Feine_Backware\" title=\"Kategorie:Feine Backware\">Feine Backware</a>",
+                "<a href=\"/wiki/Datei:Waffeln.jpg\" title=\"Datei:Waffeln.jpg\">Bild</a>",
+                "<a href=\"/wiki/Benutzer_Diskussion:Waffel\" title=\"Benutzer Diskussion:Waffel\">Diskussion</a>",
+                "<a href=\"/wiki/Spezial:ISBN-Suche/3-12-345678-9\" title=\"Spezial:ISBN-Suche\">ISBN</a>",
+            )
+            .to_string(),
+        );
+
+        let connections = waffle_page.get_connections().unwrap();
+
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].get_url().as_str(), "https://de.wikipedia.org/wiki/Teig");
+
+        let connections = waffle_page.get_connections_by_namespace().unwrap();
+
+        assert_eq!(connections[&Namespace::Category].len(), 1);
+        assert_eq!(connections[&Namespace::File].len(), 1);
+        assert_eq!(connections[&Namespace::Talk].len(), 1);
+        assert_eq!(connections[&Namespace::Special].len(), 1);
+        assert_eq!(Namespace::from_title_with_lang("Kategorie:Feine Backware", "en"), Namespace::Article);
+        assert_eq!(Namespace::from_title_with_lang("Discussion utilisateur:Gaufre", "fr"), Namespace::Talk);
+        assert_eq!(Namespace::from_title_with_lang("Die Diskussion: Ein Roman", "de"), Namespace::Article);
+    }
+
+    #[test]
+    fn test_get_see_also() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some(
+            concat!(
+                "<p><a href=\"/wiki/Batter\" title=\"Batter\">batter</a></p>",
+                "<div class=\"mw-heading mw-heading2\"><h2 id=\"See_also\">See also</h2></div>",
+                "<ul><li><a href=\"/wiki/Pancake\" title=\"Pancake\">Pancake</a></li>",
+                "<li><a href=\"/wiki/Stroopwafel\" title=\"Stroopwafel\">Stroopwafel</a></li></ul>",
+                "<div class=\"mw-heading mw-heading2\"><h2 id=\"References\">References</h2></div>",
+                "<a href=\"/wiki/Breakfast\" title=\"Breakfast\">Breakfast</a>",
+            )
+            .to_string(),
+        );
+
+        let see_also = waffle_page.get_see_also().unwrap();
+
+        assert_eq!(see_also.len(), 2);
+        assert_eq!(see_also[0].get_url().path(), "/wiki/Pancake");
+        assert_eq!(see_also[1].get_url().path(), "/wiki/Stroopwafel");
+    }
+
+    #[test]
+    fn test_get_hatnote_targets() {
+        let url = WikipediaUrl::from_path("/wiki/Mercury_(planet)".to_string()).unwrap();
+        let mut mercury_page = Page::new(url);
+
+        mercury_page.body = Some(
+            concat!(
+                "<div role=\"note\" class=\"hatnote navigation-not-searchable\">For other uses, see ",
+                "<a href=\"/wiki/Mercury_(disambiguation)\" class=\"mw-disambig\" title=\"Mercury (disambiguation)\">",
+                "Mercury (disambiguation)</a>.</div>",
+                "<p>Mercury is the first planet from the <a href=\"/wiki/Sun\" title=\"Sun\">Sun</a>.</p>",
+                "<div role=\"note\" class=\"hatnote navigation-not-searchable\">Main article: ",
+                "<a href=\"/wiki/Geology_of_Mercury\" title=\"Geology of Mercury\">Geology of Mercury</a></div>",
+            )
+            .to_string(),
+        );
+
+        let targets = mercury_page.get_hatnote_targets().unwrap();
+
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].get_url().path(), "/wiki/Mercury_(disambiguation)");
+        assert_eq!(targets[0].title.as_deref(), Some("Mercury (disambiguation)"));
+        assert_eq!(targets[1].get_url().path(), "/wiki/Geology_of_Mercury");
+    }
+
+    #[test]
+    fn test_is_disambiguation() {
+        let url = WikipediaUrl::from_path("/wiki/Mercury".to_string()).unwrap();
+        let mut mercury_page = Page::new(url);
+
+        mercury_page.body = Some(
+            concat!(
+                "<div id=\"mw-content-text\"><p><b>Mercury</b> most commonly refers to:</p>",
+                "<ul><li><a href=\"/wiki/Mercury_(planet)\" title=\"Mercury (planet)\">Mercury (planet)</a></li></ul>",
+                "<div id=\"disambigbox\" class=\"metadata plainlinks dmbox dmbox-disambig\" role=\"note\">",
+                "This disambiguation page lists articles associated with the title Mercury.</div></div>",
+            )
+            .to_string(),
+        );
+
+        assert!(mercury_page.is_disambiguation().unwrap());
+
+        let url = WikipediaUrl::from_path("/wiki/Mercury_(element)".to_string()).unwrap();
+        let mut boilerplate_page = Page::new(url);
+
+        boilerplate_page.body = Some(
+            "<div id=\"mw-content-text\"><p><b>Mercury</b> may refer to:</p></div>".to_string(),
+        );
+
+        assert!(boilerplate_page.is_disambiguation().unwrap());
+
+        let url = WikipediaUrl::from_path("/wiki/Mercury_(planet)".to_string()).unwrap();
+        let mut planet_page = Page::new(url);
+
+        planet_page.body = Some(
+            concat!(
+                "<div role=\"note\" class=\"hatnote navigation-not-searchable\">For other uses, see ",
+                "<a href=\"/wiki/Mercury_(disambiguation)\" class=\"mw-disambig\" title=\"Mercury (disambiguation)\">",
+                "Mercury (disambiguation)</a>.</div>",
+                "<div id=\"mw-content-text\"><p>Mercury is the first planet from the Sun.</p></div>",
+            )
+            .to_string(),
+        );
+
+        assert!(!planet_page.is_disambiguation().unwrap());
+    }
+
+    #[test]
+    fn test_get_external_links() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some(
+            concat!(
+                "<a href=\"/wiki/Belgium\" title=\"Belgium\">Belgium</a>",
+                "<a rel=\"nofollow\" class=\"external text\" href=\"https://www.bbc.co.uk/news/waffles\">BBC</a>",
+                "<a href=\"https://de.wikipedia.org/wiki/Waffel\" title=\"Waffel\">Deutsch</a>",
+                "<a class=\"external text\" href=\"https://doi.org/10.1000%2F182\">doi</a>",
+                "<a href=\"https://www.bbc.co.uk/news/waffles\">BBC again</a>",
+                "<a href=\"//archive.org/details/waffles\">archive</a>",
+                "<a href=\"mailto:waffles@example.com\">mail</a>",
+                "<a href=\"#cite_note-1\">[1]</a>",
+            )
+            .to_string(),
+        );
+
+        assert_eq!(
+            waffle_page
+                .get_external_links()
+                .unwrap()
+                .iter()
+                .map(Url::as_str)
+                .collect::<Vec<_>>(),
+            vec![
+                "https://www.bbc.co.uk/news/waffles",
+                "https://doi.org/10.1000%2F182",
+                "https://archive.org/details/waffles",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_thumbnail() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some(
+            "<meta property=\"og:image\" content=\"//upload.wikimedia.org/wikipedia/commons/5/5b/Waffles.jpg\">"
+                .to_string(),
+        );
+
+        assert_eq!(
+            waffle_page.get_thumbnail().unwrap().unwrap().as_str(),
+            "https://upload.wikimedia.org/wikipedia/commons/5/5b/Waffles.jpg"
+        );
+
+        let url = WikipediaUrl::from_path("/wiki/Waffle_iron_stub".to_string()).unwrap();
+        let mut stub_page = Page::new(url);
+
+        stub_page.body = Some("<meta property=\"og:title\" content=\"Waffle iron stub - Wikipedia\">".to_string());
+
+        assert_eq!(stub_page.get_thumbnail().unwrap(), None);
+    }
+
+    #[test]
+    fn test_get_thumbnail_live() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        assert!(waffle_page.get_thumbnail().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_get_categories() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some(
+            concat!(
+                "<div id=\"catlinks\" class=\"catlinks\" data-mw=\"interface\">",
+                "<div id=\"mw-normal-catlinks\" class=\"mw-normal-catlinks\">",
+                "<a href=\"/wiki/Help:Category\" title=\"Help:Category\">Categories</a>: <ul>",
+                "<li><a href=\"/wiki/Category:Belgian_cuisine\" title=\"Category:Belgian cuisine\">Belgian cuisine</a></li>",
+                "<li><a href=\"/wiki/Category:Breakfast_foods\" title=\"Category:Breakfast foods\">Breakfast foods</a></li>",
+                "</ul></div><div id=\"mw-hidden-catlinks\" class=\"mw-hidden-catlinks mw-hidden-cats-hidden\">",
+                "Hidden categories: <ul><li><a href=\"/wiki/Category:Articles_with_short_description\" ",
+                "title=\"Category:Articles with short description\">Articles with short description</a></li></ul></div></div>",
+            )
+            .to_string(),
+        );
+
+        assert_eq!(waffle_page.get_categories().unwrap(), vec!["Belgian cuisine", "Breakfast foods"]);
+        assert_eq!(waffle_page.get_hidden_categories().unwrap(), vec!["Articles with short description"]);
+    }
+
+    #[test]
+    fn test_get_categories_live() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        let categories = waffle_page.get_categories().unwrap();
+
+        assert!(!categories.is_empty());
+        assert!(categories.iter().any(|category| category.contains("food") || category.contains("cuisine")));
+    }
+
+    #[test]
+    fn test_is_list_article() {
+        let is_list_article = |path: &str| WikipediaUrl::from_path(path).unwrap().is_list_article();
+
+        assert!(is_list_article("/wiki/List_of_waffle_dishes"));
+        assert!(is_list_article("/wiki/Index_of_baking_articles"));
+        assert!(is_list_article("/wiki/glossary_of_baking"));
+        assert!(!is_list_article("/wiki/Waffle"));
+        assert!(!is_list_article("/wiki/Listeria"));
+    }
+
+    #[test]
+    fn test_get_first_sentence() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some(
+            concat!(
+                "<div id=\"mw-content-text\"><p class=\"mw-empty-elt\">\n</p>",
+                "<p>A <b>waffle</b> is a dish made from leavened <a href=\"/wiki/Batter\">batter</a>",
+                " popularized in the U.S. by Dr. J. Smith.<sup id=\"cite_ref-1\" class=\"reference\"><a href=\"#cite_note-1\">",
+                "<span class=\"cite-bracket\">&#91;</span>1<span class=\"cite-bracket\">&#93;</span></a></sup>",
+                " Waffles are eaten throughout the world.</p></div>",
+            )
+            .to_string(),
+        );
+
+        assert_eq!(
+            waffle_page.get_first_sentence().unwrap(),
+            "A waffle is a dish made from leavened batter popularized in the U.S. by Dr. J. Smith."
+        );
+
+        waffle_page.body = Some(CITED_LEAD.to_string());
+
+        assert_eq!(waffle_page.get_first_sentence().unwrap(), "A waffle is a dish made from leavened batter.");
+    }
+
+    #[test]
+    fn test_get_summary() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        let summary = waffle_page.get_summary().unwrap();
+
+        assert!(!summary.is_empty());
+        assert!(summary.to_lowercase().contains("waffle"));
+        assert!(!summary.contains('<'));
+    }
+
+    /// The lead of a body with the reference markup Wikipedia really uses, brackets escaped in their own spans
+    const CITED_LEAD: &str = concat!(
+        "<div id=\"mw-content-text\"><p class=\"mw-empty-elt\">\n</p>",
+        "<p>A <b>waffle</b> is a dish made from leavened <a href=\"/wiki/Batter\">batter</a>.",
+        "<sup id=\"cite_ref-1\" class=\"reference\"><a href=\"#cite_note-1\"><span class=\"cite-bracket\">&#91;</span>1",
+        "<span class=\"cite-bracket\">&#93;</span></a></sup> Waffles are eaten throughout the world.",
+        "<sup class=\"reference\"><a href=\"#cite_note-2\">&#91;note 2&#93;</a></sup></p></div>",
+    );
+
+    #[test]
+    fn test_get_summary_strips_references() {
+        let mut waffle_page = Page::new(WikipediaUrl::from_path("/wiki/Waffle").unwrap());
+        waffle_page.body = Some(CITED_LEAD.to_string());
+
+        assert_eq!(
+            waffle_page.get_summary().unwrap(),
+            "A waffle is a dish made from leavened batter. Waffles are eaten throughout the world."
+        );
+        assert_eq!(html_to_text("<p>Fried&#91;1&#93; dough</p>"), "Fried dough");
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        let html = "<p>Fish&#160;&amp; <b>chips</b> &#x2014; &quot;a&nbsp;dish&quot;&#39;s &lt;plate&gt;</p>";
+
+        assert_eq!(html_to_text(html), "Fish & chips \u{2014} \"a dish\"'s <plate>");
+    }
+
+    #[test]
+    fn test_get_article_text() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        waffle_page.body = Some(
+            concat!(
+                "<p>Jump to content</p><div id=\"mw-content-text\"><div class=\"mw-parser-output\">",
+                "<p class=\"mw-empty-elt\">\n</p><p>A <b>waffle</b> is a dish.<sup id=\"cite_ref-1\" class=\"reference\">",
+                "<a href=\"#cite_note-1\">&#91;1&#93;</a></sup> It is eaten hot.",
+                "<sup class=\"noprint Inline-Template Template-Fact\">[<i><a href=\"/wiki/Wikipedia:Citation_needed\" ",
+                "title=\"Wikipedia:Citation needed\">citation needed</a></i>]</sup></p>",
+                "<div class=\"mw-heading mw-heading2\"><h2 id=\"History\">History</h2>",
+                "<span class=\"mw-editsection\"><span class=\"mw-editsection-bracket\">[</span>",
+                "<a href=\"/w/index.php?title=Waffle&amp;action=edit&amp;section=1\">edit</a>",
+                "<span class=\"mw-editsection-bracket\">]</span></span></div>",
+                "<p><style data-mw-deduplicate=\"TemplateStyles:r1\">.mw-parser-output .frac{white-space:nowrap}</style>",
+                "Waffles covered 2 m<sup>2</sup> of plates.<sup class=\"reference\"><a href=\"#cite_note-2\">[2]</a></sup></p>",
+                "<script>document.write('[3]')</script>",
+                "<table class=\"navbox\"><tr><td>Waffle navigation</td></tr></table>",
+                "</div></div><div id=\"catlinks\" class=\"catlinks\"><p>Categories: Waffles</p></div>",
+            )
+            .to_string(),
+        );
+
+        let text = waffle_page.get_article_text().unwrap();
+
+        assert_eq!(text, "A waffle is a dish. It is eaten hot.\nWaffles covered 2 m2 of plates.");
+        assert!(!text.contains("[edit]"));
+        assert!(!regex::Regex::new(r"\[\d+\]").unwrap().is_match(&text));
+        assert_eq!(waffle_page.get_word_count().unwrap(), 15);
+    }
+
+    #[test]
+    fn test_stream_connections() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        let streamed = waffle_page
+            .stream_connections()
+            .unwrap()
+            .map(|page| page.unwrap().get_url().clone())
+            .collect::<Vec<_>>();
+
+        let connections = waffle_page
+            .get_connections()
+            .unwrap()
+            .into_iter()
+            .map(|page| page.get_url().clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(streamed, connections)
+    }
+
+    #[test]
+    fn test_stream_connections_in_chunks() {
+        let body = concat!(
+            "<a href=\"/wiki/Waffle\"\n title=\"Waffle\">waffle</a>\n",
+            "<!-- <a href=\"/wiki/Commented\" title=\"Commented\"> -->",
+            "<script>let link = '<a href=\"/wiki/Scripted\" title=\"Scripted\">';</script>",
+            "<a href=\"/wiki/Bj%C3%B8rn_Borg\" title=\"Bjørn Borg\" data-note=\"a > b\">Borg</a>",
+            "<a href=\"/wiki/Waffle\" title=\"Waffle\">again</a><p>1 < 2</p>",
+            "<a\nhref=\"/wiki/Belgium\"\ntitle=\"Belgium\">Belgium</a>",
+        );
+
+        let source = MockSource::new(body);
+        let page = Page::with_source(WikipediaUrl::from_path("/wiki/Batter").unwrap(), source);
+
+        let paths = |pages: Vec<Page>| pages.iter().map(|page| page.get_url().path().to_string()).collect::<Vec<_>>();
+
+        let expected = paths(Page::get_connections_from_body(body, "en"));
+
+        assert_eq!(expected, ["/wiki/Waffle", "/wiki/Bj%C3%B8rn_Borg", "/wiki/Belgium"]);
+        assert_eq!(paths(page.stream_connections().unwrap().map(Result::unwrap).collect()), expected);
+
+        for chunk_len in 1..16 {
+            let reader: Box<dyn Read + Send> = Box::new(Cursor::new(body));
+            let stream = ConnectionStream::new(BufReader::with_capacity(chunk_len, reader), page.clone());
+
+            assert_eq!(paths(stream.map(Result::unwrap).collect()), expected, "chunks of {chunk_len} bytes");
+        }
+
+        let footer = format!("{body}<div id=\"catlinks\"><a href=\"/wiki/Pancake\" title=\"Pancake\"></div>");
+        let mut partial = Page::with_source(WikipediaUrl::from_path("/wiki/Batter").unwrap(), MockSource::new(&footer));
+
+        assert_eq!(paths(partial.get_connections_partial(10).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_reachable_count_depth_zero() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let waffle_page = Page::new(url);
+
+        assert_eq!(Page::reachable_count(&waffle_page, 0).unwrap(), 1)
+    }
+
+    #[test]
+    fn test_reachable_count() {
+        let source = MockSource::with_bodies("", &[
+            ("/wiki/Waffle", "<a href=\"/wiki/Belgium\" title=\"Belgium\">a</a><a href=\"/wiki/Batter\" title=\"Batter\">b</a>"),
+            ("/wiki/Belgium", "<a href=\"/wiki/waffle\" title=\"Waffle\">a</a><a href=\"/wiki/Batter\" title=\"Batter\">b</a><a href=\"/wiki/Europe\" title=\"Europe\">c</a>"),
+            ("/wiki/Batter", "<a href=\"/wiki/Belgium\" title=\"Belgium\">a</a>"),
+        ]);
+
+        let waffle_page = Page::with_source(WikipediaUrl::from_path("/wiki/Waffle").unwrap(), source.clone());
+
+        assert_eq!(Page::reachable_count(&waffle_page, 1).unwrap(), 3);
+        assert_eq!(source.fetched().len(), 1);
+
+        assert_eq!(Page::reachable_count(&waffle_page, 2).unwrap(), 4);
+        assert_eq!(source.fetched().len(), 1 + 3);
+    }
+
+    #[test]
+    fn test_get_connections_partial() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        let connections = waffle_page.get_connections_partial(5).unwrap();
+
+        assert_eq!(connections.len(), 5);
+        assert!(waffle_page.body.is_none())
     }
 }
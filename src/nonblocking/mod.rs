@@ -0,0 +1,129 @@
+//! Async variants of the [Page] methods that load the body, using the non-blocking [reqwest::Client]
+//!
+//! Pages with the default [Fetcher](crate::fetch::Fetcher) are loaded with a shared async client, which doesn't
+//! apply the fetcher's rate limit and retries: limit the number of requests in flight yourself when crawling
+//! concurrently. Pages with a [BodySource](crate::fetch::BodySource) or [Fetcher](crate::fetch::Fetcher) of their
+//! own are loaded through it instead, see [Page::load_body_async]
+
+use std::thread;
+
+use reqwest::Url;
+
+use super::fetch::DEFAULT_ASYNC_CLIENT;
+use super::{Page, PageError, ReqwestError};
+
+impl Page {
+    /// Load the body of the wikipedia page into the struct without blocking, see [Page::load_body]
+    ///
+    /// Error responses like `404 Not Found` or `429 Too Many Requests` fail with [PageError::RequestError] rather
+    /// than being loaded as the body. A page given its own source with [Page::with_source], [Page::with_fetcher] or
+    /// [Page::with_client] is loaded through it, and since those are blocking this waits for it on a separate
+    /// thread, blocking the task's executor thread until the body arrives
+    pub async fn load_body_async(&mut self) -> Result<(), PageError> {
+        if self.body.is_some() {
+            return Ok(());
+        }
+
+        if self.source.is_some() || self.fetcher.is_some() {
+            // Blocking clients can't be used from inside an async runtime, so don't call them from this thread
+            let body = thread::scope(|scope| {
+                scope
+                    .spawn(|| self.source().fetch(self.get_url()))
+                    .join()
+                    .expect("Thread loading the body panicked")
+            })?;
+
+            self.body = Some(body);
+
+            return Ok(());
+        }
+
+        self.body = Some(fetch_async(&DEFAULT_ASYNC_CLIENT, self.get_url().clone()).await?);
+
+        Ok(())
+    }
+
+    /// Get the title of the Wikipedia page without blocking, loading it and the body as well if necessary, see
+    /// [Page::get_title]
+    pub async fn get_title_async(&mut self) -> Result<String, PageError> {
+        self.load_body_async().await?;
+
+        self.get_title()
+    }
+
+    /// Get a list of [Page]s for all of the Wikipedia links on the page without blocking, loading the body as
+    /// well if necessary, see [Page::get_connections]
+    pub async fn get_connections_async(&mut self) -> Result<Vec<Page>, PageError> {
+        self.load_body_async().await?;
+
+        self.get_connections()
+    }
+}
+
+/// Send a `GET` request to `url` with `client` and read the whole body as text, failing on error statuses
+async fn fetch_async(client: &reqwest::Client, url: Url) -> Result<String, ReqwestError> {
+    client.get(url).send().await?.error_for_status()?.text().await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use reqwest::{StatusCode, Url};
+
+    use super::fetch_async;
+    use crate::tests::MockSource;
+    use crate::{Page, WikipediaUrl};
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_get_title_async() {
+        let url = WikipediaUrl::from_path("/wiki/Waffle".to_string()).unwrap();
+        let mut waffle_page = Page::new(url);
+
+        let title = runtime().block_on(waffle_page.get_title_async()).unwrap();
+
+        assert_eq!(title.as_str(), "Waffle");
+        assert!(!waffle_page.get_connections().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_load_body_async_uses_source() {
+        let source = MockSource::new("<title>Waffle - Wikipedia</title><a href=\"/wiki/Belgium\" title=\"Belgium\">b</a>");
+        let mut waffle_page = Page::with_source(WikipediaUrl::from_path("/wiki/Waffle").unwrap(), source.clone());
+
+        let connections = runtime().block_on(waffle_page.get_connections_async()).unwrap();
+
+        assert_eq!(connections.len(), 1);
+        assert_eq!(waffle_page.get_title().unwrap(), "Waffle");
+        assert_eq!(source.fetched().len(), 1);
+    }
+
+    #[test]
+    fn test_fetch_async_error_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/wiki/Waffle", listener.local_addr().unwrap())).unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 1024]);
+
+            let body = "<title>Not Found</title>";
+            write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        });
+
+        let error = runtime().block_on(fetch_async(&reqwest::Client::new(), url)).unwrap_err();
+
+        assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+
+        server.join().unwrap();
+    }
+}
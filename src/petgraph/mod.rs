@@ -1,41 +1,2208 @@
-use petgraph_lib::{stable_graph::{StableGraph, NodeIndex}};
-use super::{Page, ReqwestError};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::hash_map::{Entry, RandomState};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
-pub struct WikipediaGraph<NodeIndex = petgraph_lib::stable_graph::DefaultIx, EdgeIndex = petgraph_lib::Directed> {
-    graph: StableGraph<Page, (), EdgeIndex, NodeIndex>,
+use petgraph_lib::{algo::all_simple_paths, stable_graph::{StableGraph, NodeIndex}, visit::{EdgeRef, IntoEdgeReferences}, Direction};
+use reqwest::Url;
+use thiserror::Error;
+use super::{Page, PageError, WikipediaUrl, WikipediaUrlInvalidError};
+
+#[cfg(feature = "api")]
+use super::api::{ApiClient, ApiError, api_title, backlinks_of};
+
+/// This error covers all failures when loading a [WikipediaGraph] from an edge list, see
+/// [WikipediaGraph::from_edge_list_csv]
+#[derive(Error, Debug)]
+pub enum EdgeListError {
+    #[error("failed to read the edge list: '{0}'")]
+    IoError(#[from] io::Error),
+    #[error("the row on line {0} of the edge list doesn't have exactly two fields")]
+    MalformedRowError(usize),
+    #[error("the row on line {0} of the edge list has an empty title")]
+    EmptyTitleError(usize),
+    #[error("edge list title couldn't be made into a url: '{0}'")]
+    InvalidTitleError(#[from] WikipediaUrlInvalidError),
+}
+
+/// The relationship between two [Page]s that an edge of a [WikipediaGraph] represents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgeKind {
+    /// The source page links to the target page
+    Link,
+    /// The two pages are both linked to by this many articles
+    CoCitation(usize),
+    /// The source page lists the target page in its "See also" section
+    SeeAlso,
+}
+
+/// Bodies shorter than this are allowed to have no links without triggering the [NoLinksPolicy]
+const NON_TRIVIAL_BODY_LEN: usize = 2048;
+
+/// The number of pages [WikipediaGraph::expand_pages] loads at once unless set otherwise
+const DEFAULT_MAX_THREADS: usize = 4;
+
+/// This error covers all failures when expanding a [WikipediaGraph]
+#[derive(Error, Debug)]
+pub enum CrawlError {
+    #[error("failed to load the page: '{0}'")]
+    PageError(#[from] PageError),
+    #[error("no links were found on '{0}', it may have failed to parse")]
+    NoLinksFoundError(String),
+    #[error("the graph reached its budget of {0} nodes, see WikipediaGraph::set_max_total_nodes")]
+    NodeBudgetReachedError(usize),
+}
+
+/// What to do when expanding a page finds no links in a non-trivial body, see [CrawlOptions::on_no_links]
+///
+/// A fetched article should normally link somewhere, so this usually means the page failed to parse or isn't an
+/// article at all
+#[derive(Clone, Default)]
+pub enum NoLinksPolicy {
+    /// Record the page as a dead end without saying anything
+    #[default]
+    Ignore,
+    /// Call the function with the page, then record it as a dead end, see [NoLinksPolicy::warn]
+    Warn(Arc<dyn Fn(&Page) + Send + Sync>),
+    /// Fail the expansion with [CrawlError::NoLinksFoundError]
+    Error,
+}
+
+impl NoLinksPolicy {
+    /// Call `warn` with every page found without links, like logging it or collecting it to retry later
+    pub fn warn(warn: impl Fn(&Page) + Send + Sync + 'static) -> Self {
+        NoLinksPolicy::Warn(Arc::new(warn))
+    }
+}
+
+impl fmt::Debug for NoLinksPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoLinksPolicy::Ignore => f.write_str("Ignore"),
+            NoLinksPolicy::Warn(_) => f.write_str("Warn(..)"),
+            NoLinksPolicy::Error => f.write_str("Error"),
+        }
+    }
+}
+
+/// Options controlling which pages are added when expanding a [WikipediaGraph]
+#[derive(Debug, Clone)]
+pub struct CrawlOptions {
+    skip_list_articles: bool,
+    on_no_links: NoLinksPolicy,
+    max_threads: usize,
+    keep_bodies: bool,
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        CrawlOptions {
+            skip_list_articles: false,
+            on_no_links: NoLinksPolicy::default(),
+            max_threads: DEFAULT_MAX_THREADS,
+            keep_bodies: false,
+        }
+    }
+}
+
+impl CrawlOptions {
+    /// Create new [CrawlOptions] with every option disabled, ignoring pages without links and loading up to 4 pages
+    /// at once
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the most pages [WikipediaGraph::expand_pages] loads at once, `0` is treated as `1`
+    ///
+    /// The [Fetcher](crate::fetch::Fetcher) of the pages still applies its own rate and connection limits on top
+    /// of this
+    pub fn max_threads(mut self, max_threads: usize) -> Self {
+        self.max_threads = max_threads;
+        self
+    }
+
+    /// Set what to do when an expanded page has a non-trivial body but no links, see [NoLinksPolicy]
+    pub fn on_no_links(mut self, policy: NoLinksPolicy) -> Self {
+        self.on_no_links = policy;
+        self
+    }
+
+    /// Set whether to keep the bodies fetched while expanding pages in their nodes
+    ///
+    /// Without this a body is dropped once its links have been added, so expanding the same page again (like
+    /// [WikipediaGraph::expand_see_also] after [WikipediaGraph::expand_page]) fetches it again, but a large crawl
+    /// doesn't hold every article in memory
+    pub fn keep_bodies(mut self, keep: bool) -> Self {
+        self.keep_bodies = keep;
+        self
+    }
+
+    /// Set whether to skip list and index articles when expanding, see [WikipediaUrl::is_list_article]
+    ///
+    /// [WikipediaUrl::is_list_article]: crate::WikipediaUrl::is_list_article
+    pub fn skip_list_articles(mut self, skip: bool) -> Self {
+        self.skip_list_articles = skip;
+        self
+    }
+
+    /// Check if a connection should be left out of the graph
+    fn skips(&self, page: &Page) -> bool {
+        self.skip_list_articles && page.url.is_list_article()
+    }
+}
+
+/// Options controlling how nodes are labelled when exporting a [WikipediaGraph] to DOT or GraphML
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    strip_disambiguators: bool,
+}
+
+impl ExportOptions {
+    /// Create new [ExportOptions] with every option disabled, labelling nodes with their full article title
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether to strip the parenthetical disambiguator from the labels of nodes
+    ///
+    /// By default `Mercury (planet)` and `Mercury (element)` keep their disambiguators so they can be told apart,
+    /// with this enabled both are labelled with the display title `Mercury`
+    pub fn strip_disambiguators(mut self, strip: bool) -> Self {
+        self.strip_disambiguators = strip;
+        self
+    }
+
+    /// Get the label of a node in an export
+    fn label(&self, page: &Page) -> String {
+        let label = node_label(page);
+
+        if !self.strip_disambiguators {
+            return label;
+        }
+
+        match label.trim_end().strip_suffix(')').and_then(|rest| rest.rsplit_once(" (")) {
+            Some((display, _)) if !display.is_empty() => display.to_string(),
+            _ => label,
+        }
+    }
+}
+
+/// A graph of Wikipedia [Page]s, optionally storing a `Data` payload of your own alongside each node
+///
+/// Node data lives in a side-table keyed by [NodeIndex]. Since the graph is backed by a [StableGraph], the index
+/// of a node never changes when other nodes are removed, so data stays attached to the right node; the data of a
+/// node removed with [WikipediaGraph::remove_page] is dropped with it, and nodes merged by
+/// [WikipediaGraph::normalize_and_dedup] keep the data of the surviving node, falling back to the data of the
+/// merged one
+pub struct WikipediaGraph<NodeIndex = petgraph_lib::stable_graph::DefaultIx, EdgeIndex = petgraph_lib::Directed, Data = ()> {
+    graph: StableGraph<Page, EdgeKind, EdgeIndex, NodeIndex>,
+    node_data: HashMap<petgraph_lib::stable_graph::NodeIndex<NodeIndex>, Data>,
+    /// The node of each normalized url, see [WikipediaGraph::node_for_url]
+    nodes: HashMap<Url, petgraph_lib::stable_graph::NodeIndex<NodeIndex>>,
+    options: CrawlOptions,
+    /// See [WikipediaGraph::set_max_total_nodes]
+    max_total_nodes: Option<usize>,
+}
+
+/// The serialized form of a [WikipediaGraph]: its nodes with their data, and its edges between positions in `nodes`
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerializedGraph<P, D> {
+    nodes: Vec<(P, Option<D>)>,
+    edges: Vec<(usize, usize, EdgeKind)>,
+}
+
+/// Serializes the pages (see [Page]), node data and edges, but not the [CrawlOptions] or the node budget
+#[cfg(feature = "serde")]
+impl<Ix: petgraph_lib::stable_graph::IndexType, D: serde::Serialize> serde::Serialize
+    for WikipediaGraph<Ix, petgraph_lib::Directed, D>
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let positions: HashMap<NodeIndex<Ix>, usize> = self
+            .graph
+            .node_indices()
+            .enumerate()
+            .map(|(position, index)| (index, position))
+            .collect();
+
+        let serialized = SerializedGraph {
+            nodes: self
+                .graph
+                .node_indices()
+                .map(|index| (&self.graph[index], self.node_data.get(&index)))
+                .collect(),
+            edges: self
+                .graph
+                .edge_references()
+                .map(|edge| (positions[&edge.source()], positions[&edge.target()], *edge.weight()))
+                .collect(),
+        };
+
+        serde::Serialize::serialize(&serialized, serializer)
+    }
+}
+
+/// Deserializes a graph with the default [CrawlOptions] and no node budget
+///
+/// Nodes are numbered in the order they were serialized, so indices can differ from the original graph if nodes
+/// had been removed from it
+#[cfg(feature = "serde")]
+impl<'de, Ix: petgraph_lib::stable_graph::IndexType, D: serde::Deserialize<'de>> serde::Deserialize<'de>
+    for WikipediaGraph<Ix, petgraph_lib::Directed, D>
+{
+    fn deserialize<De: serde::Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        let serialized: SerializedGraph<Page, D> = serde::Deserialize::deserialize(deserializer)?;
+
+        let mut graph = WikipediaGraph::new();
+
+        let indices: Vec<NodeIndex<Ix>> = serialized
+            .nodes
+            .into_iter()
+            .map(|(page, data)| {
+                let index = graph.insert_node(page);
+
+                if let Some(data) = data {
+                    graph.node_data.insert(index, data);
+                }
+
+                index
+            })
+            .collect();
+
+        for (source, target, kind) in serialized.edges {
+            let (Some(source), Some(target)) = (indices.get(source), indices.get(target)) else {
+                return Err(serde::de::Error::custom(format!(
+                    "edge from node {source} to node {target} is out of bounds"
+                )));
+            };
+
+            graph.graph.add_edge(*source, *target, kind);
+        }
+
+        Ok(graph)
+    }
 }
 
-impl<Ix: petgraph_lib::stable_graph::IndexType> WikipediaGraph<Ix> {
+impl<Ix: petgraph_lib::stable_graph::IndexType, D> Default for WikipediaGraph<Ix, petgraph_lib::Directed, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Ix: petgraph_lib::stable_graph::IndexType, D> WikipediaGraph<Ix, petgraph_lib::Directed, D> {
     pub fn new() -> Self {
         WikipediaGraph {
-            graph: StableGraph::default()
+            graph: StableGraph::default(),
+            node_data: HashMap::new(),
+            nodes: HashMap::new(),
+            options: CrawlOptions::default(),
+            max_total_nodes: None,
+        }
+    }
+
+    /// Create a new [WikipediaGraph] that expands pages according to `options`
+    pub fn with_options(options: CrawlOptions) -> Self {
+        WikipediaGraph {
+            options,
+            ..Self::new()
+        }
+    }
+
+    /// Get the [CrawlOptions] used when expanding pages
+    pub fn get_options(&self) -> &CrawlOptions {
+        &self.options
+    }
+
+    /// Replace the [CrawlOptions] used when expanding pages
+    pub fn set_options(&mut self, options: CrawlOptions) {
+        self.options = options;
+    }
+
+    /// Get the most nodes expanding pages may grow the graph to, see [WikipediaGraph::set_max_total_nodes]
+    pub fn get_max_total_nodes(&self) -> Option<usize> {
+        self.max_total_nodes
+    }
+
+    /// Set the most nodes expanding pages may grow the graph to, or `None` for no limit
+    ///
+    /// Once the graph has this many nodes, [WikipediaGraph::expand_page], [WikipediaGraph::expand_pages],
+    /// [WikipediaGraph::expand_see_also] and [WikipediaGraph::crawl_from] still add edges to the pages already in the graph, but fail with
+    /// [CrawlError::NodeBudgetReachedError] if a page links to any new ones. Nodes can still be added directly with
+    /// [WikipediaGraph::add_page]
+    pub fn set_max_total_nodes(&mut self, max_total_nodes: Option<usize>) {
+        self.max_total_nodes = max_total_nodes;
+    }
+
+    /// Attach `data` to the node at `index`, returning the data it replaced
+    ///
+    /// Fails with `data` given back if there's no node at `index`, like after [WikipediaGraph::remove_page]
+    pub fn set_node_data(&mut self, index: NodeIndex<Ix>, data: D) -> Result<Option<D>, D> {
+        if !self.graph.contains_node(index) {
+            return Err(data);
         }
+
+        Ok(self.node_data.insert(index, data))
     }
 
-    pub fn add_page(&mut self, page: Page) {
-        self.graph.add_node(page);
+    /// Get the data attached to the node at `index`
+    pub fn get_node_data(&self, index: NodeIndex<Ix>) -> Option<&D> {
+        self.node_data.get(&index)
     }
 
-    pub fn expand_page(&mut self, index: NodeIndex<Ix>) -> Result<(), ReqwestError> {
-        let mut weight: Page  = self.graph.node_weight(index).expect("Index doesn't exist").clone();
+    /// Get the data attached to the node at `index` mutably
+    pub fn get_node_data_mut(&mut self, index: NodeIndex<Ix>) -> Option<&mut D> {
+        self.node_data.get_mut(&index)
+    }
 
-        let _ = weight.load_title();
+    /// Remove and return the data attached to the node at `index`
+    pub fn remove_node_data(&mut self, index: NodeIndex<Ix>) -> Option<D> {
+        self.node_data.remove(&index)
+    }
 
-        let connections = weight.get_connections()?;
+    /// Get the number of pages in the graph
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
 
-        let mut graph = &mut self.graph;
+    /// Get the number of edges in the graph, counting parallel edges of different [EdgeKind]s separately
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
 
-        connections
-            .into_iter()
-            .for_each(|connection| {
-                let connection_index = graph.add_node(connection);
+    /// Get the title of every page in the graph that's known without fetching anything
+    ///
+    /// Like [Page::try_get_title] the title is read out of the body if only the body is loaded, and it's `None`
+    /// if neither is. Nothing is stored in the pages, so this only needs shared access to the graph
+    ///
+    /// [Page::try_get_title]: crate::Page::try_get_title
+    pub fn loaded_titles(&self) -> Vec<(NodeIndex<Ix>, Option<String>)> {
+        self.graph
+            .node_indices()
+            .map(|index| {
+                let page = &self.graph[index];
+
+                let title = page
+                    .title
+                    .clone()
+                    .or_else(|| page.body.as_deref().and_then(Page::get_title_from_body));
+
+                (index, title)
+            })
+            .collect()
+    }
+
+    /// Add a node for `page` to the graph, returning its index
+    ///
+    /// If a page with the same url is already in the graph (see [WikipediaGraph::node_for_url]) its node is
+    /// returned instead and `page` is dropped
+    pub fn add_page(&mut self, page: Page) -> NodeIndex<Ix> {
+        match self.node_for_url(&page.url) {
+            Some(index) => index,
+            None => self.insert_node(page),
+        }
+    }
+
+    /// Remove the node at `index` from the graph with its edges and data, returning its page
+    ///
+    /// The indices of the other nodes don't change, see [WikipediaGraph]
+    pub fn remove_page(&mut self, index: NodeIndex<Ix>) -> Option<Page> {
+        let page = self.graph.remove_node(index)?;
+
+        self.node_data.remove(&index);
+
+        if let Entry::Occupied(entry) = self.nodes.entry(page.url.normalized().get_url().clone())
+            && *entry.get() == index
+        {
+            entry.remove();
+        }
+
+        Some(page)
+    }
+
+    /// Get the index of the node for the page at `url`, if it's in the graph
+    ///
+    /// Urls are compared after normalizing them (see [WikipediaUrl::normalized]), so different spellings of the
+    /// same article find the same node
+    pub fn node_for_url(&self, url: &WikipediaUrl) -> Option<NodeIndex<Ix>> {
+        self.nodes.get(url.normalized().get_url()).copied()
+    }
+
+    /// Add a node for `page` without checking for an existing one, keeping track of its url
+    fn insert_node(&mut self, page: Page) -> NodeIndex<Ix> {
+        let url = page.url.normalized().get_url().clone();
+        let index = self.graph.add_node(page);
+
+        self.nodes.entry(url).or_insert(index);
+
+        index
+    }
+
+    /// Add an [EdgeKind::Link] edge from the page at `index` to each of the pages it links to, loading its body if
+    /// necessary
+    ///
+    /// Linked pages that are already in the graph get an edge to their existing node instead of a new node, and links
+    /// from the page to itself are skipped. Fails with [CrawlError::NodeBudgetReachedError] after adding what fits if
+    /// the page links to new pages past the node budget, see [WikipediaGraph::set_max_total_nodes]
+    pub fn expand_page(&mut self, index: NodeIndex<Ix>) -> Result<(), CrawlError> {
+        self.expand_page_within(index, EdgeKind::Link, Page::get_connections_from_body, usize::MAX)
+    }
+
+    /// Breadth-first crawl from `start` until every page within `depth` links of it has been added, or the graph
+    /// has `max_nodes` nodes
+    ///
+    /// Each page is expanded with [WikipediaGraph::expand_page] at most once, and `start` reuses its node if it's
+    /// already in the graph. A `depth` of `0` only adds `start`. The first page that fails to load stops the crawl
+    /// with its error, leaving the pages added so far in the graph, and so does reaching the node budget (see
+    /// [WikipediaGraph::set_max_total_nodes]), which `max_nodes` doesn't. Returns the index of the node of `start`
+    pub fn crawl_from(&mut self, start: Page, depth: usize, max_nodes: usize) -> Result<NodeIndex<Ix>, CrawlError> {
+        if let Some(budget) = self.max_total_nodes
+            && self.graph.node_count() >= budget
+            && self.node_for_url(&start.url).is_none()
+        {
+            return Err(CrawlError::NodeBudgetReachedError(budget));
+        }
+
+        let start = self.add_page(start);
+
+        let mut expanded = HashSet::from([start]);
+        let mut queue = VecDeque::from([(start, 0)]);
+
+        while let Some((index, distance)) = queue.pop_front() {
+            if distance >= depth || self.graph.node_count() >= max_nodes {
+                continue;
+            }
+
+            self.expand_page_within(index, EdgeKind::Link, Page::get_connections_from_body, max_nodes)?;
+
+            for neighbour in self.graph.neighbors_directed(index, Direction::Outgoing) {
+                if expanded.insert(neighbour) {
+                    queue.push_back((neighbour, distance + 1));
+                }
+            }
+        }
+
+        Ok(start)
+    }
+
+    /// Expand each of the pages at `indices` like [WikipediaGraph::expand_page], loading up to
+    /// [CrawlOptions::max_threads] of them at once
+    ///
+    /// Only loading the pages happens in parallel, the connections are added to the graph afterwards in the order of
+    /// `indices`, so the graph ends up the same as after expanding the pages one by one. If a page fails to load the
+    /// pages before it are still added, and the error of the first one that failed is returned
+    pub fn expand_pages(&mut self, indices: &[NodeIndex<Ix>]) -> Result<(), CrawlError> {
+        let pages: Vec<&Page> = indices
+            .iter()
+            .map(|index| self.graph.node_weight(*index).expect("Index doesn't exist"))
+            .collect();
+
+        let threads = self.options.max_threads.clamp(1, pages.len().max(1));
+        let next = AtomicUsize::new(0);
 
-                // Todo: Error logic like everywhere
-                graph.try_add_edge(index, connection_index, ()).unwrap();
-            });
+        let mut loaded: Vec<_> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut loaded = Vec::new();
+
+                        loop {
+                            let position = next.fetch_add(1, Ordering::Relaxed);
+
+                            let Some(page) = pages.get(position) else {
+                                break;
+                            };
+
+                            loaded.push((position, load_connections(page, Page::get_connections_from_body)));
+                        }
+
+                        loaded
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("Thread loading pages panicked"))
+                .collect()
+        });
+
+        loaded.sort_by_key(|(position, _)| *position);
+
+        for (index, (_, result)) in indices.iter().zip(loaded) {
+            self.add_connections(*index, result?, EdgeKind::Link, usize::MAX)?;
+        }
+
+        Ok(())
+    }
+
+    /// Expand the page at `index` like [WikipediaGraph::expand_page] with `kind` edges to the pages `extract` finds
+    /// in its body (given the page's language), without adding nodes past `max_nodes`
+    fn expand_page_within(
+        &mut self,
+        index: NodeIndex<Ix>,
+        kind: EdgeKind,
+        extract: fn(&str, &str) -> Vec<Page>,
+        max_nodes: usize,
+    ) -> Result<(), CrawlError> {
+        let node = self.graph.node_weight(index).expect("Index doesn't exist");
+
+        let loaded = load_connections(node, extract)?;
+
+        self.add_connections(index, loaded, kind, max_nodes)
+    }
+
+    /// Add the connections `loaded` for the page at `index` as `kind` edges, without adding nodes past `max_nodes`,
+    /// and give the page the title (and body, see [CrawlOptions::keep_bodies]) found while loading if it has none
+    fn add_connections(
+        &mut self,
+        index: NodeIndex<Ix>,
+        loaded: LoadedConnections,
+        kind: EdgeKind,
+        max_nodes: usize,
+    ) -> Result<(), CrawlError> {
+        let LoadedConnections { connections, body_len, title, body } = loaded;
+
+        let node = &mut self.graph[index];
+
+        if node.title.is_none() {
+            node.title = title;
+        }
+
+        if self.options.keep_bodies && node.body.is_none() {
+            node.body = body;
+        }
+
+        let node = &self.graph[index];
+        let url = node.url.normalized();
+
+        // Only a page without any links at all is suspicious, plenty of articles have no "See also" section
+        if kind == EdgeKind::Link && connections.is_empty() && body_len >= NON_TRIVIAL_BODY_LEN {
+            match &self.options.on_no_links {
+                NoLinksPolicy::Ignore => {}
+                NoLinksPolicy::Warn(warn) => warn(node),
+                NoLinksPolicy::Error => return Err(CrawlError::NoLinksFoundError(node.get_url().to_string())),
+            }
+        }
+
+        let budget = self.max_total_nodes.unwrap_or(usize::MAX);
+        let mut over_budget = false;
+
+        for connection in connections {
+            if self.options.skips(&connection) || connection.url.normalized().get_url() == url.get_url() {
+                continue;
+            }
+
+            let connection_index = match self.node_for_url(&connection.url) {
+                Some(existing) => existing,
+                None if self.graph.node_count() < max_nodes.min(budget) => self.insert_node(connection),
+                None => {
+                    over_budget |= self.graph.node_count() >= budget;
+                    continue;
+                }
+            };
+
+            if connection_index == index {
+                continue;
+            }
+
+            let linked = self
+                .graph
+                .edges_connecting(index, connection_index)
+                .any(|edge| *edge.weight() == kind);
+
+            if !linked {
+                self.graph.add_edge(index, connection_index, kind);
+            }
+        }
+
+        if over_budget {
+            return Err(CrawlError::NodeBudgetReachedError(budget));
+        }
 
         Ok(())
+    }
+
+    /// Add the links in the "See also" section of the page at `index` to the graph as [EdgeKind::SeeAlso] edges
+    ///
+    /// Unlike [WikipediaGraph::expand_page] this only follows the links the article's editors picked as related,
+    /// building a curated-relatedness network. Otherwise pages are added the same way: existing nodes are reused,
+    /// self-links and repeated edges are skipped and the node budget applies (see
+    /// [WikipediaGraph::set_max_total_nodes])
+    pub fn expand_see_also(&mut self, index: NodeIndex<Ix>) -> Result<(), CrawlError> {
+        self.expand_page_within(index, EdgeKind::SeeAlso, Page::get_see_also_from_body, usize::MAX)
+    }
+
+    /// Normalize the url of every node (see [WikipediaUrl::normalized]) and merge the nodes that end up equal
+    ///
+    /// This is a one-shot cleanup for graphs built before normalization was applied. For each set of equal
+    /// nodes the one with the lowest index is kept, picking up a title or body from the others if it lacks one,
+    /// and the edges of the others are re-pointed at it. Edges that would duplicate an existing edge or turn into
+    /// a self-loop are dropped. Returns the number of nodes that were merged away
+    ///
+    /// [WikipediaUrl::normalized]: crate::WikipediaUrl::normalized
+    pub fn normalize_and_dedup(&mut self) -> usize {
+        let mut kept: HashMap<Url, NodeIndex<Ix>> = HashMap::new();
+        let mut duplicates: Vec<(NodeIndex<Ix>, NodeIndex<Ix>)> = Vec::new();
+
+        let indices = self.graph.node_indices().collect::<Vec<_>>();
+
+        for index in indices {
+            let page = &mut self.graph[index];
+
+            page.url = page.url.normalized();
+
+            match kept.entry(page.get_url().clone()) {
+                Entry::Occupied(entry) => duplicates.push((index, *entry.get())),
+                Entry::Vacant(entry) => {
+                    entry.insert(index);
+                }
+            }
+        }
+
+        for &(duplicate, keeper) in &duplicates {
+            let edges = self
+                .graph
+                .edges_directed(duplicate, Direction::Outgoing)
+                .map(|edge| (keeper, edge.target(), *edge.weight()))
+                .chain(
+                    self.graph
+                        .edges_directed(duplicate, Direction::Incoming)
+                        .map(|edge| (edge.source(), keeper, *edge.weight())),
+                )
+                .collect::<Vec<_>>();
+
+            for (source, target, kind) in edges {
+                let source = if source == duplicate { keeper } else { source };
+                let target = if target == duplicate { keeper } else { target };
+
+                let exists = self
+                    .graph
+                    .edges_connecting(source, target)
+                    .any(|edge| *edge.weight() == kind);
+
+                if source != target && !exists {
+                    self.graph.add_edge(source, target, kind);
+                }
+            }
+
+            let duplicate_page = self.graph.remove_node(duplicate).expect("Index doesn't exist");
+            let keeper_page = &mut self.graph[keeper];
+
+            if keeper_page.title.is_none() {
+                keeper_page.title = duplicate_page.title;
+            }
+
+            if keeper_page.body.is_none() {
+                keeper_page.body = duplicate_page.body;
+            }
+
+            if let Some(data) = self.node_data.remove(&duplicate) {
+                self.node_data.entry(keeper).or_insert(data);
+            }
+        }
+
+        self.nodes = self
+            .graph
+            .node_indices()
+            .map(|index| (self.graph[index].get_url().clone(), index))
+            .collect();
+
+        duplicates.len()
+    }
+
+    /// Build the co-citation graph of `seeds`, using the MediaWiki API
+    ///
+    /// Two articles are co-cited when a third article links to both of them. The graph contains a node for each
+    /// seed, in order, and an [EdgeKind::CoCitation] edge from the earlier to the later seed of every co-cited pair,
+    /// weighted by the number of articles linking to both. Every seed is looked up on the language of the first one
+    #[cfg(feature = "api")]
+    pub fn cocitation_graph(seeds: &[Page]) -> Result<Self, ApiError> {
+        let mut graph = Self::new();
+
+        let Some(first) = seeds.first() else {
+            return Ok(graph);
+        };
+
+        let titles = seeds.iter().map(api_title).collect::<Result<Vec<_>, _>>()?;
+
+        let backlinks = backlinks_of(&ApiClient::of(first), first.url.lang(), &titles)?
+            .into_iter()
+            .map(|pages| pages.into_iter().map(|page| page.get_url().clone()).collect())
+            .collect::<Vec<HashSet<Url>>>();
+
+        let indices = seeds
+            .iter()
+            .map(|seed| graph.insert_node(seed.clone()))
+            .collect::<Vec<_>>();
+
+        for i in 0..seeds.len() {
+            for j in i + 1..seeds.len() {
+                let count = backlinks[i].intersection(&backlinks[j]).count();
+
+                if count > 0 {
+                    graph.graph.add_edge(indices[i], indices[j], EdgeKind::CoCitation(count));
+                }
+            }
+        }
+
+        Ok(graph)
+    }
+
+    /// Get every simple directed path from `from` to `to` with at most `max_len` edges, as lists of node indices
+    ///
+    /// The number of paths can grow exponentially with `max_len` on densely linked graphs, so keep the bound small
+    /// (3 or 4 hops is already a lot on a crawled graph). Parallel edges of different [EdgeKind]s only count once
+    pub fn all_paths(&self, from: NodeIndex<Ix>, to: NodeIndex<Ix>, max_len: usize) -> Vec<Vec<NodeIndex<Ix>>> {
+        if from == to {
+            return vec![vec![from]];
+        }
+
+        let Some(max_intermediate_nodes) = max_len.checked_sub(1) else {
+            return Vec::new();
+        };
+
+        let mut seen = HashSet::new();
+
+        all_simple_paths::<Vec<_>, _, RandomState>(&self.graph, from, to, 0, Some(max_intermediate_nodes))
+            .filter(|path| seen.insert(path.clone()))
+            .collect()
+    }
+
+    /// Get a shortest directed path from `from` to `to` as a list of node indices, or `None` if `to` can't be
+    /// reached
+    ///
+    /// This is the "degrees of separation" between two pages: the path has one more node than the number of links
+    /// that have to be followed. Edges of every [EdgeKind] are followed
+    pub fn shortest_path(&self, from: NodeIndex<Ix>, to: NodeIndex<Ix>) -> Option<Vec<NodeIndex<Ix>>> {
+        if !self.graph.contains_node(from) || !self.graph.contains_node(to) {
+            return None;
+        }
+
+        let mut previous: HashMap<NodeIndex<Ix>, NodeIndex<Ix>> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(index) = queue.pop_front() {
+            if index == to {
+                let mut path = vec![to];
+
+                while let Some(before) = previous.get(path.last()?) {
+                    path.push(*before);
+                }
+
+                path.reverse();
+
+                return Some(path);
+            }
+
+            for neighbour in self.graph.neighbors_directed(index, Direction::Outgoing) {
+                if neighbour != from && !previous.contains_key(&neighbour) {
+                    previous.insert(neighbour, index);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Get the titles of the pages along a path of node indices, like one from [WikipediaGraph::shortest_path]
+    ///
+    /// Pages without a loaded title fall back to the title in their url, and indices not in the graph are skipped
+    pub fn path_titles(&self, path: &[NodeIndex<Ix>]) -> Vec<String> {
+        path.iter()
+            .filter_map(|index| self.graph.node_weight(*index))
+            .map(node_label)
+            .collect()
+    }
+
+    /// Get the [PageRank] of every node in the graph, with a damping factor of `0.85`
+    ///
+    /// Parallel edges count once per edge, and nodes without outgoing links spread their rank evenly over the
+    /// whole graph. The scores sum to `1.0`
+    ///
+    /// [PageRank]: https://en.wikipedia.org/wiki/PageRank
+    pub fn pagerank(&self) -> HashMap<NodeIndex<Ix>, f64> {
+        const DAMPING: f64 = 0.85;
+        const MAX_ITERATIONS: usize = 100;
+        const TOLERANCE: f64 = 1e-10;
+
+        let count = self.graph.node_count() as f64;
+
+        let mut ranks: HashMap<NodeIndex<Ix>, f64> =
+            self.graph.node_indices().map(|index| (index, 1.0 / count)).collect();
+
+        for _ in 0..MAX_ITERATIONS {
+            let dangling = ranks
+                .iter()
+                .filter(|(index, _)| self.graph.edges_directed(**index, Direction::Outgoing).next().is_none())
+                .map(|(_, rank)| rank)
+                .sum::<f64>();
+
+            let base = (1.0 - DAMPING) / count + DAMPING * dangling / count;
+
+            let mut next: HashMap<NodeIndex<Ix>, f64> = ranks.keys().map(|index| (*index, base)).collect();
+
+            for (index, rank) in &ranks {
+                let out_degree = self.graph.edges_directed(*index, Direction::Outgoing).count();
+
+                for edge in self.graph.edges_directed(*index, Direction::Outgoing) {
+                    *next.get_mut(&edge.target()).expect("Edge target doesn't exist") +=
+                        DAMPING * rank / out_degree as f64;
+                }
+            }
+
+            let change = next.iter().map(|(index, rank)| (rank - ranks[index]).abs()).sum::<f64>();
+
+            ranks = next;
+
+            if change < TOLERANCE {
+                break;
+            }
+        }
 
-        
+        ranks
     }
-}
\ No newline at end of file
+
+    /// Get the `n` nodes with the highest [PageRank](WikipediaGraph::pagerank), with their scores and titles,
+    /// sorted from highest to lowest
+    pub fn top_pagerank(&self, n: usize) -> Vec<(NodeIndex<Ix>, f64, String)> {
+        let mut ranks = self.pagerank().into_iter().collect::<Vec<_>>();
+
+        ranks.sort_by(|(a_index, a_rank), (b_index, b_rank)| b_rank.total_cmp(a_rank).then(a_index.cmp(b_index)));
+
+        ranks
+            .into_iter()
+            .take(n)
+            .map(|(index, rank)| (index, rank, node_label(&self.graph[index])))
+            .collect()
+    }
+
+    /// Get the number of edges leaving the node at `index`, counting parallel edges of different [EdgeKind]s
+    /// separately
+    pub fn out_degree(&self, index: NodeIndex<Ix>) -> usize {
+        self.graph.neighbors_directed(index, Direction::Outgoing).count()
+    }
+
+    /// Get the number of edges pointing to the node at `index`, counting parallel edges of different [EdgeKind]s
+    /// separately
+    pub fn in_degree(&self, index: NodeIndex<Ix>) -> usize {
+        self.graph.neighbors_directed(index, Direction::Incoming).count()
+    }
+
+    /// Get the `n` nodes with the most incoming edges (see [WikipediaGraph::in_degree]), with their in-degrees and
+    /// titles, sorted from most to least linked
+    ///
+    /// Nodes with the same in-degree are ordered by index
+    pub fn most_linked(&self, n: usize) -> Vec<(NodeIndex<Ix>, usize, String)> {
+        let mut degrees = self
+            .graph
+            .node_indices()
+            .map(|index| (index, self.in_degree(index)))
+            .collect::<Vec<_>>();
+
+        degrees.sort_by(|(a_index, a_degree), (b_index, b_degree)| b_degree.cmp(a_degree).then(a_index.cmp(b_index)));
+
+        degrees
+            .into_iter()
+            .take(n)
+            .map(|(index, degree)| (index, degree, node_label(&self.graph[index])))
+            .collect()
+    }
+
+    /// Get the degree assortativity of the graph: the Pearson correlation between the out-degree of the source and the
+    /// in-degree of the target across every edge
+    ///
+    /// Positive values mean well-linking articles tend to link to well-linked articles, negative values mean hubs
+    /// tend to link to sparsely linked articles. Returns [f64::NAN] if the graph has no edges or either degree
+    /// doesn't vary across them
+    pub fn degree_assortativity(&self) -> f64 {
+        let degrees = self
+            .graph
+            .edge_indices()
+            .map(|edge| {
+                let (source, target) = self.graph.edge_endpoints(edge).expect("Edge doesn't exist");
+
+                (
+                    self.graph.neighbors_directed(source, Direction::Outgoing).count() as f64,
+                    self.graph.neighbors_directed(target, Direction::Incoming).count() as f64,
+                )
+            })
+            .collect::<Vec<(f64, f64)>>();
+
+        if degrees.is_empty() {
+            return f64::NAN;
+        }
+
+        let count = degrees.len() as f64;
+        let mean_out = degrees.iter().map(|(outgoing, _)| outgoing).sum::<f64>() / count;
+        let mean_in = degrees.iter().map(|(_, incoming)| incoming).sum::<f64>() / count;
+
+        let (covariance, variance_out, variance_in) = degrees.iter().fold(
+            (0.0, 0.0, 0.0),
+            |(covariance, variance_out, variance_in), (outgoing, incoming)| {
+                let (outgoing, incoming) = (outgoing - mean_out, incoming - mean_in);
+
+                (
+                    covariance + outgoing * incoming,
+                    variance_out + outgoing * outgoing,
+                    variance_in + incoming * incoming,
+                )
+            },
+        );
+
+        covariance / (variance_out * variance_in).sqrt()
+    }
+
+    /// Get the local clustering coefficient of the node at `index`: the fraction of pairs of its neighbours that
+    /// are linked to each other
+    ///
+    /// Computed on the undirected projection of the graph, so link direction, parallel edges and self-loops are
+    /// ignored. Nodes with fewer than two neighbours have a coefficient of `0.0`
+    pub fn clustering_coefficient(&self, index: NodeIndex<Ix>) -> f64 {
+        let neighbours = self.undirected_neighbours(index);
+
+        if neighbours.len() < 2 {
+            return 0.0;
+        }
+
+        let linked_pairs = neighbours
+            .iter()
+            .map(|neighbour| {
+                self.undirected_neighbours(*neighbour)
+                    .intersection(&neighbours)
+                    .count()
+            })
+            .sum::<usize>()
+            / 2;
+
+        let possible_pairs = neighbours.len() * (neighbours.len() - 1) / 2;
+
+        linked_pairs as f64 / possible_pairs as f64
+    }
+
+    /// Get the mean [clustering coefficient](WikipediaGraph::clustering_coefficient) over every node in the graph
+    ///
+    /// Returns [f64::NAN] if the graph has no nodes
+    pub fn average_clustering(&self) -> f64 {
+        let count = self.graph.node_count();
+
+        if count == 0 {
+            return f64::NAN;
+        }
+
+        self.graph
+            .node_indices()
+            .map(|index| self.clustering_coefficient(index))
+            .sum::<f64>()
+            / count as f64
+    }
+
+    /// The distinct neighbours of `index` in either direction, not counting itself
+    fn undirected_neighbours(&self, index: NodeIndex<Ix>) -> HashSet<NodeIndex<Ix>> {
+        self.graph
+            .neighbors_undirected(index)
+            .filter(|neighbour| *neighbour != index)
+            .collect()
+    }
+
+    /// Stream the graph to `w` in the [DOT] format, labelling each node with its display title
+    ///
+    /// [DOT]: https://graphviz.org/doc/info/lang.html
+    pub fn write_dot<W: Write>(&self, w: W) -> io::Result<()> {
+        self.write_dot_with_options(w, &ExportOptions::default())
+    }
+
+    /// Stream the graph to `w` in the [DOT] format, labelling each node according to `options`
+    ///
+    /// [DOT]: https://graphviz.org/doc/info/lang.html
+    pub fn write_dot_with_options<W: Write>(&self, mut w: W, options: &ExportOptions) -> io::Result<()> {
+        writeln!(w, "digraph {{")?;
+
+        for index in self.graph.node_indices() {
+            writeln!(
+                w,
+                "    {} [label=\"{}\"];",
+                index.index(),
+                escape_dot(&options.label(&self.graph[index]))
+            )?;
+        }
+
+        for edge in (&self.graph).edge_references() {
+            match edge.weight() {
+                EdgeKind::Link => writeln!(w, "    {} -> {};", edge.source().index(), edge.target().index())?,
+                EdgeKind::CoCitation(count) => writeln!(
+                    w,
+                    "    {} -> {} [weight={count}];",
+                    edge.source().index(),
+                    edge.target().index()
+                )?,
+                EdgeKind::SeeAlso => writeln!(
+                    w,
+                    "    {} -> {} [label=\"see also\"];",
+                    edge.source().index(),
+                    edge.target().index()
+                )?,
+            }
+        }
+
+        writeln!(w, "}}")
+    }
+
+    /// Get the graph in the [DOT] format, see [WikipediaGraph::write_dot]
+    ///
+    /// [DOT]: https://graphviz.org/doc/info/lang.html
+    pub fn to_dot(&self) -> String {
+        let mut buffer = Vec::new();
+
+        self.write_dot(&mut buffer).expect("Writing to a Vec can't fail");
+
+        String::from_utf8(buffer).expect("DOT output is always valid UTF-8")
+    }
+
+    /// Get the graph in the [DOT] format, see [WikipediaGraph::write_dot_with_options]
+    ///
+    /// [DOT]: https://graphviz.org/doc/info/lang.html
+    pub fn to_dot_with_options(&self, options: &ExportOptions) -> String {
+        let mut buffer = Vec::new();
+
+        self.write_dot_with_options(&mut buffer, options).expect("Writing to a Vec can't fail");
+
+        String::from_utf8(buffer).expect("DOT output is always valid UTF-8")
+    }
+
+    /// Stream the graph to `w` in the [GraphML] format, with `title` and `url` attributes on each node
+    ///
+    /// The `title` attribute is the display title of the node
+    ///
+    /// [GraphML]: http://graphml.graphdrawing.org/
+    pub fn write_graphml<W: Write>(&self, w: W) -> io::Result<()> {
+        self.write_graphml_with_options(w, &ExportOptions::default())
+    }
+
+    /// Stream the graph to `w` in the [GraphML] format, labelling each node according to `options`
+    ///
+    /// [GraphML]: http://graphml.graphdrawing.org/
+    pub fn write_graphml_with_options<W: Write>(&self, mut w: W, options: &ExportOptions) -> io::Result<()> {
+        writeln!(w, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(w, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">")?;
+        writeln!(w, "  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>")?;
+        writeln!(w, "  <key id=\"url\" for=\"node\" attr.name=\"url\" attr.type=\"string\"/>")?;
+        writeln!(w, "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"long\"/>")?;
+        writeln!(w, "  <key id=\"kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>")?;
+        writeln!(w, "  <graph id=\"G\" edgedefault=\"directed\">")?;
+
+        for index in self.graph.node_indices() {
+            let page = &self.graph[index];
+
+            writeln!(
+                w,
+                "    <node id=\"n{}\"><data key=\"title\">{}</data><data key=\"url\">{}</data></node>",
+                index.index(),
+                escape_xml(&options.label(page)),
+                escape_xml(page.get_url().as_str())
+            )?;
+        }
+
+        for edge in (&self.graph).edge_references() {
+            match edge.weight() {
+                EdgeKind::Link => writeln!(
+                    w,
+                    "    <edge source=\"n{}\" target=\"n{}\"/>",
+                    edge.source().index(),
+                    edge.target().index()
+                )?,
+                EdgeKind::CoCitation(count) => writeln!(
+                    w,
+                    "    <edge source=\"n{}\" target=\"n{}\"><data key=\"weight\">{count}</data></edge>",
+                    edge.source().index(),
+                    edge.target().index()
+                )?,
+                EdgeKind::SeeAlso => writeln!(
+                    w,
+                    "    <edge source=\"n{}\" target=\"n{}\"><data key=\"kind\">see_also</data></edge>",
+                    edge.source().index(),
+                    edge.target().index()
+                )?,
+            }
+        }
+
+        writeln!(w, "  </graph>")?;
+        writeln!(w, "</graphml>")
+    }
+
+    /// Get the graph in the [GraphML] format, see [WikipediaGraph::write_graphml]
+    ///
+    /// [GraphML]: http://graphml.graphdrawing.org/
+    pub fn to_graphml(&self) -> String {
+        let mut buffer = Vec::new();
+
+        self.write_graphml(&mut buffer).expect("Writing to a Vec can't fail");
+
+        String::from_utf8(buffer).expect("GraphML output is always valid UTF-8")
+    }
+
+    /// Get the graph in the [GraphML] format, see [WikipediaGraph::write_graphml_with_options]
+    ///
+    /// [GraphML]: http://graphml.graphdrawing.org/
+    pub fn to_graphml_with_options(&self, options: &ExportOptions) -> String {
+        let mut buffer = Vec::new();
+
+        self.write_graphml_with_options(&mut buffer, options).expect("Writing to a Vec can't fail");
+
+        String::from_utf8(buffer).expect("GraphML output is always valid UTF-8")
+    }
+
+    /// Stream the edges of the graph to `w` as CSV rows of `source,target` titles, preceded by a header row
+    pub fn write_edge_csv<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "source,target")?;
+
+        for edge in self.graph.edge_indices() {
+            let (source, target) = self.graph.edge_endpoints(edge).expect("Edge doesn't exist");
+
+            writeln!(
+                w,
+                "{},{}",
+                escape_csv(&node_label(&self.graph[source])),
+                escape_csv(&node_label(&self.graph[target]))
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the edges of the graph as CSV, see [WikipediaGraph::write_edge_csv]
+    pub fn to_edge_csv(&self) -> String {
+        let mut buffer = Vec::new();
+
+        self.write_edge_csv(&mut buffer).expect("Writing to a Vec can't fail");
+
+        String::from_utf8(buffer).expect("CSV output is always valid UTF-8")
+    }
+
+    /// Stream the edges of the graph to `w` as CSV, see [WikipediaGraph::write_edge_csv]
+    #[deprecated(note = "renamed to `write_edge_csv` to match `write_node_csv`")]
+    pub fn write_edge_list_csv<W: Write>(&self, w: W) -> io::Result<()> {
+        self.write_edge_csv(w)
+    }
+
+    /// Get the edges of the graph as CSV, see [WikipediaGraph::to_edge_csv]
+    #[deprecated(note = "renamed to `to_edge_csv` to match `to_node_csv`")]
+    pub fn to_edge_list_csv(&self) -> String {
+        self.to_edge_csv()
+    }
+
+    /// Stream the nodes of the graph to `w` as CSV rows of `node_id,title,url`, preceded by a header row
+    ///
+    /// The id is the index of the node in the graph, and the title is the same as in
+    /// [WikipediaGraph::write_edge_csv], so the two files can be joined on it
+    pub fn write_node_csv<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "node_id,title,url")?;
+
+        for index in self.graph.node_indices() {
+            let page = &self.graph[index];
+
+            writeln!(
+                w,
+                "{},{},{}",
+                index.index(),
+                escape_csv(&node_label(page)),
+                escape_csv(page.get_url().as_str())
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the nodes of the graph as CSV, see [WikipediaGraph::write_node_csv]
+    pub fn to_node_csv(&self) -> String {
+        let mut buffer = Vec::new();
+
+        self.write_node_csv(&mut buffer).expect("Writing to a Vec can't fail");
+
+        String::from_utf8(buffer).expect("CSV output is always valid UTF-8")
+    }
+
+    /// Load a graph from a CSV edge list of `source,target` title pairs, like the output of
+    /// [WikipediaGraph::write_edge_csv]
+    ///
+    /// A node is created for each distinct page, with its [WikipediaUrl] built from the title (see
+    /// [WikipediaUrl::from_title]), and every row becomes an [EdgeKind::Link] edge. Titles are matched like
+    /// [WikipediaGraph::add_page] matches urls, so spellings of the same article like `waffle` and `Waffle` share the
+    /// node of the first one, and rows repeating an edge are only added once. A leading `source,target` header row
+    /// and blank lines are skipped
+    ///
+    /// The input is read a line at a time, so a large edge list is never held in memory whole. Errors about a row
+    /// give the line it starts on, and a row with an empty title fails with [EdgeListError::EmptyTitleError]
+    pub fn from_edge_list_csv<R: Read>(reader: R) -> Result<Self, EdgeListError> {
+        let mut graph = Self::new();
+        let mut record = String::new();
+        let mut record_line = 0;
+        let mut first = true;
+
+        for (line_index, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+
+            if record.is_empty() {
+                record_line = line_index + 1;
+            } else {
+                record.push('\n');
+            }
+
+            record.push_str(&line);
+
+            // A quoted field with a line break continues on the next line, until its quotes are balanced
+            if !record.matches('"').count().is_multiple_of(2) {
+                continue;
+            }
+
+            let fields = parse_csv_row(&std::mem::take(&mut record));
+
+            if fields.iter().all(|field| field.is_empty()) {
+                continue;
+            }
+
+            if std::mem::take(&mut first) && fields == ["source", "target"] {
+                continue;
+            }
+
+            let [source, target] =
+                <[String; 2]>::try_from(fields).map_err(|_| EdgeListError::MalformedRowError(record_line))?;
+
+            let mut node = |title: String| -> Result<NodeIndex<Ix>, EdgeListError> {
+                if title.trim().is_empty() {
+                    return Err(EdgeListError::EmptyTitleError(record_line));
+                }
+
+                Ok(graph.add_page(Page::new_with_title(WikipediaUrl::from_title(&title)?, title)))
+            };
+
+            let (source, target) = (node(source)?, node(target)?);
+
+            let linked = graph
+                .graph
+                .edges_connecting(source, target)
+                .any(|edge| *edge.weight() == EdgeKind::Link);
+
+            if !linked {
+                graph.graph.add_edge(source, target, EdgeKind::Link);
+            }
+        }
+
+        Ok(graph)
+    }
+}
+
+impl<Ix: petgraph_lib::stable_graph::IndexType, D: Clone> WikipediaGraph<Ix, petgraph_lib::Directed, D> {
+    /// Get the subgraph induced by the nodes titled one of `titles`, with the edges between them
+    ///
+    /// Nodes are matched on their loaded title, or the title in their url if it isn't loaded, so no pages are
+    /// fetched. The returned graph has its own indices, and keeps the node data and [CrawlOptions] of this graph
+    pub fn subgraph_by_titles(&self, titles: &[&str]) -> Self {
+        let titles = titles.iter().copied().collect::<HashSet<&str>>();
+
+        let mut subgraph = Self::with_options(self.options.clone());
+        let mut remapped: HashMap<NodeIndex<Ix>, NodeIndex<Ix>> = HashMap::new();
+
+        for index in self.graph.node_indices() {
+            let page = &self.graph[index];
+
+            if !titles.contains(node_label(page).as_str()) {
+                continue;
+            }
+
+            let new_index = subgraph.insert_node(page.clone());
+
+            if let Some(data) = self.node_data.get(&index) {
+                subgraph.node_data.insert(new_index, data.clone());
+            }
+
+            remapped.insert(index, new_index);
+        }
+
+        for edge in (&self.graph).edge_references() {
+            if let (Some(source), Some(target)) = (remapped.get(&edge.source()), remapped.get(&edge.target())) {
+                subgraph.graph.add_edge(*source, *target, *edge.weight());
+            }
+        }
+
+        subgraph
+    }
+}
+
+/// What [load_connections] found on a page
+struct LoadedConnections {
+    connections: Vec<Page>,
+    /// The length of the body the connections were found in
+    body_len: usize,
+    /// The title of the page, if its body had to be fetched and the title was found in it
+    title: Option<String>,
+    /// The body of the page, if it had to be fetched
+    body: Option<String>,
+}
+
+/// Get the connections `extract` finds in the body of `page` to expand it with
+///
+/// A fetched body without a title is still expanded rather than failing: the links are all expanding needs, and
+/// the node is left without a title so [Page::get_title] reports the error if it's asked for later
+fn load_connections(page: &Page, extract: fn(&str, &str) -> Vec<Page>) -> Result<LoadedConnections, PageError> {
+    // Borrow the body if it's already loaded, otherwise fetch it into a copy without one rather than cloning the
+    // whole page
+    match &page.body {
+        Some(body) => Ok(LoadedConnections {
+            connections: page.inherit_wiki(extract(body, page.url.lang())),
+            body_len: body.len(),
+            title: None,
+            body: None,
+        }),
+        None => {
+            let mut fetched = page.without_body();
+
+            fetched.load_body()?;
+
+            let body = fetched.body.take().unwrap_or_default();
+
+            Ok(LoadedConnections {
+                connections: fetched.inherit_wiki(extract(&body, fetched.url.lang())),
+                body_len: body.len(),
+                title: Page::get_title_from_body(&body),
+                body: Some(body),
+            })
+        }
+    }
+}
+
+/// Get the title of a node without fetching anything, falling back to the title in the url when it isn't loaded
+fn node_label(page: &Page) -> String {
+    match &page.title {
+        Some(title) => title.clone(),
+        None => page.url.title_from_url(),
+    }
+}
+
+fn escape_dot(input: &str) -> String {
+    input.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn escape_csv(input: &str) -> String {
+    if input.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", input.replace('"', "\"\""))
+    } else {
+        input.to_string()
+    }
+}
+
+/// Split a single CSV row into its fields, handling quoted fields with escaped quotes and line breaks
+fn parse_csv_row(row: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = row.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+
+    fields.push(field);
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use petgraph_lib::Direction;
+    use petgraph_lib::visit::{EdgeRef, IntoEdgeReferences};
+
+    use super::{CrawlError, CrawlOptions, EdgeKind, ExportOptions, NoLinksPolicy, WikipediaGraph};
+    use crate::tests::MockSource;
+    use crate::{Page, WikipediaUrl};
+
+    fn titled_page(path: &str, title: &str) -> Page {
+        Page::new_with_title(WikipediaUrl::from_path(path).unwrap(), title.to_string())
+    }
+
+    fn two_node_graph() -> WikipediaGraph {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let waffle = graph.graph.add_node(titled_page("/wiki/Waffle", "Waffle"));
+        let belgium = graph.graph.add_node(titled_page("/wiki/Belgium", "Belgium, \"the\" country"));
+        graph.graph.add_edge(waffle, belgium, EdgeKind::Link);
+
+        graph
+    }
+
+    #[test]
+    fn test_normalize_and_dedup() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let waffle = graph.graph.add_node(Page::new(WikipediaUrl::from_path("/wiki/Waffle").unwrap()));
+        let waffle_history = graph
+            .graph
+            .add_node(titled_page("/wiki/waffle#History", "Waffle"));
+        let belgium = graph.graph.add_node(titled_page("/wiki/Belgium", "Belgium"));
+        let syrup = graph.graph.add_node(titled_page("/wiki/Syrup", "Syrup"));
+
+        graph.graph.add_edge(waffle, belgium, EdgeKind::Link);
+        graph.graph.add_edge(waffle_history, belgium, EdgeKind::Link);
+        graph.graph.add_edge(waffle_history, waffle, EdgeKind::Link);
+        graph.graph.add_edge(syrup, waffle_history, EdgeKind::Link);
+
+        assert_eq!(graph.normalize_and_dedup(), 1);
+
+        assert_eq!(graph.graph.node_count(), 3);
+        assert_eq!(graph.graph.edge_count(), 2);
+        assert!(graph.graph.find_edge(waffle, belgium).is_some());
+        assert!(graph.graph.find_edge(syrup, waffle).is_some());
+        assert_eq!(graph.graph[waffle].title.as_deref(), Some("Waffle"));
+        assert_eq!(graph.graph[waffle].get_url().as_str(), "https://en.wikipedia.org/wiki/Waffle");
+    }
+
+    #[test]
+    fn test_node_data_survives_merge() {
+        let mut graph: WikipediaGraph<u32, petgraph_lib::Directed, &str> = WikipediaGraph::new();
+
+        let waffle = graph.graph.add_node(titled_page("/wiki/Waffle", "Waffle"));
+        let waffle_history = graph.graph.add_node(titled_page("/wiki/waffle#History", "Waffle"));
+        let belgium = graph.graph.add_node(titled_page("/wiki/Belgium", "Belgium"));
+
+        graph.set_node_data(waffle_history, "breakfast").unwrap();
+        graph.set_node_data(belgium, "country").unwrap();
+
+        assert_eq!(graph.normalize_and_dedup(), 1);
+        assert_eq!(graph.get_node_data(waffle), Some(&"breakfast"));
+        assert_eq!(graph.get_node_data(waffle_history), None);
+        assert_eq!(graph.get_node_data(belgium), Some(&"country"));
+        assert_eq!(graph.set_node_data(waffle_history, "merged"), Err("merged"));
+    }
+
+    #[test]
+    fn test_remove_page() {
+        let mut graph: WikipediaGraph<u32, petgraph_lib::Directed, &str> = WikipediaGraph::new();
+
+        let waffle = graph.add_page(titled_page("/wiki/Waffle", "Waffle"));
+        let belgium = graph.add_page(titled_page("/wiki/Belgium", "Belgium"));
+        graph.graph.add_edge(waffle, belgium, EdgeKind::Link);
+
+        graph.set_node_data(waffle, "breakfast").unwrap();
+        graph.set_node_data(belgium, "country").unwrap();
+
+        let removed = graph.remove_page(waffle).unwrap();
+
+        assert_eq!(removed.get_url().path(), "/wiki/Waffle");
+        assert_eq!(graph.node_count(), 1);
+        assert_eq!(graph.edge_count(), 0);
+        assert_eq!(graph.get_node_data(waffle), None);
+        assert_eq!(graph.get_node_data(belgium), Some(&"country"));
+        assert_eq!(graph.node_for_url(&WikipediaUrl::from_path("/wiki/Waffle").unwrap()), None);
+        assert!(graph.remove_page(waffle).is_none());
+
+        // The freed index may be reused, but not with the data of the removed node
+        let waffle_again = graph.add_page(titled_page("/wiki/Waffle", "Waffle"));
+
+        assert_eq!(graph.node_for_url(&WikipediaUrl::from_path("/wiki/Waffle").unwrap()), Some(waffle_again));
+        assert_eq!(graph.get_node_data(waffle_again), None);
+    }
+
+    #[test]
+    fn test_expand_see_also() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let mut waffle = titled_page("/wiki/Waffle", "Waffle");
+        waffle.body = Some(
+            concat!(
+                "<a href=\"/wiki/Batter\" title=\"Batter\">batter</a>",
+                "<h2 id=\"See_also\">See also</h2>",
+                "<a href=\"/wiki/Pancake\" title=\"Pancake\">Pancake</a>",
+                "<a href=\"/wiki/Waffle\" title=\"Waffle\">Waffle</a>",
+                "<h2 id=\"References\">References</h2>",
+            )
+            .to_string(),
+        );
+
+        let waffle = graph.add_page(waffle);
+
+        graph.expand_see_also(waffle).unwrap();
+        graph.expand_see_also(waffle).unwrap();
+
+        assert_eq!(graph.graph.node_count(), 2);
+        assert_eq!(graph.graph.edge_count(), 1);
+
+        let edge = graph.graph.edges(waffle).next().unwrap();
+
+        assert_eq!(*edge.weight(), EdgeKind::SeeAlso);
+        assert_eq!(graph.graph[edge.target()].get_url().path(), "/wiki/Pancake");
+    }
+
+    #[test]
+    fn test_expand_see_also_reuses_kept_body() {
+        let source = MockSource::new(concat!(
+            "<a href=\"/wiki/Batter\" title=\"Batter\">batter</a>",
+            "<h2 id=\"See_also\">See also</h2>",
+            "<a href=\"/wiki/Pancake\" title=\"Pancake\">Pancake</a>",
+            "<a href=\"/wiki/Stroopwafel\" title=\"Stroopwafel\">Stroopwafel</a>",
+        ));
+
+        let mut graph: WikipediaGraph = WikipediaGraph::with_options(CrawlOptions::new().keep_bodies(true));
+
+        let waffle = graph.add_page(Page::with_source(WikipediaUrl::from_path("/wiki/Waffle").unwrap(), source.clone()));
+
+        graph.expand_page(waffle).unwrap();
+        graph.expand_see_also(waffle).unwrap();
+
+        assert_eq!(source.fetched().len(), 1);
+        assert_eq!(graph.graph.node_count(), 4);
+        assert_eq!(graph.graph.edges(waffle).filter(|edge| *edge.weight() == EdgeKind::SeeAlso).count(), 2);
+
+        let mut budget_graph: WikipediaGraph = WikipediaGraph::new();
+        budget_graph.set_max_total_nodes(Some(2));
+
+        let waffle = budget_graph.add_page(Page::with_source(WikipediaUrl::from_path("/wiki/Waffle").unwrap(), source));
+
+        assert!(matches!(budget_graph.expand_see_also(waffle), Err(CrawlError::NodeBudgetReachedError(2))));
+        assert_eq!(budget_graph.graph.node_count(), 2);
+    }
+
+    #[test]
+    fn test_expand_page() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let body = concat!(
+            "<a href=\"/wiki/Batter\" title=\"Batter\">batter</a>",
+            "<a href=\"/wiki/Belgium\" title=\"Belgium\">Belgium</a>",
+        );
+
+        let mut waffle = titled_page("/wiki/Waffle", "Waffle");
+        waffle.body = Some(body.to_string());
+
+        graph.add_page(waffle);
+
+        let waffle = graph.graph.node_indices().next().unwrap();
+
+        graph.expand_page(waffle).unwrap();
+
+        assert_eq!(graph.graph.node_count(), 3);
+        assert_eq!(graph.graph.neighbors(waffle).count(), 2);
+        assert_eq!(graph.graph[waffle].body.as_deref(), Some(body));
+    }
+
+    #[test]
+    fn test_expand_page_picks_up_fetched_title() {
+        let source = MockSource::with_bodies("<a href=\"/wiki/Belgium\" title=\"Belgium\">b</a>", &[(
+            "/wiki/Waffle",
+            "<title>Waffle - Wikipedia</title><a href=\"/wiki/Batter\" title=\"Batter\">a</a>",
+        )]);
+
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let waffle = graph.add_page(Page::with_source(WikipediaUrl::from_path("/wiki/Waffle").unwrap(), source.clone()));
+        let untitled = graph.add_page(Page::with_source(WikipediaUrl::from_path("/wiki/Pancake").unwrap(), source));
+
+        graph.expand_page(waffle).unwrap();
+        graph.expand_page(untitled).unwrap();
+
+        assert_eq!(graph.graph[waffle].title.as_deref(), Some("Waffle"));
+        assert_eq!(graph.graph[waffle].body, None);
+        assert_eq!(graph.graph[untitled].title, None);
+        assert_eq!(graph.graph.node_count(), 4);
+    }
+
+    #[test]
+    fn test_expand_pages() {
+        let source = MockSource::with_bodies("", &[
+            ("/wiki/Waffle", "<a href=\"/wiki/Batter\" title=\"Batter\">a</a><a href=\"/wiki/Belgium\" title=\"Belgium\">b</a>"),
+            ("/wiki/Pancake", "<a href=\"/wiki/Batter\" title=\"Batter\">a</a><a href=\"/wiki/Flour\" title=\"Flour\">b</a>"),
+            ("/wiki/Stroopwafel", "<a href=\"/wiki/Waffle\" title=\"Waffle\">a</a>"),
+        ]);
+
+        let build = || {
+            let mut graph: WikipediaGraph = WikipediaGraph::with_options(CrawlOptions::new().max_threads(2));
+
+            let indices: Vec<_> = ["/wiki/Waffle", "/wiki/Pancake", "/wiki/Stroopwafel"]
+                .into_iter()
+                .map(|path| graph.add_page(Page::with_source(WikipediaUrl::from_path(path).unwrap(), source.clone())))
+                .collect();
+
+            (graph, indices)
+        };
+
+        let (mut parallel, indices) = build();
+        parallel.expand_pages(&indices).unwrap();
+
+        let (mut sequential, indices) = build();
+        for index in &indices {
+            sequential.expand_page(*index).unwrap();
+        }
+
+        assert_eq!(parallel.graph.node_count(), 6);
+        assert_eq!(parallel.graph.edge_count(), 5);
+        assert_eq!(parallel.to_dot(), sequential.to_dot());
+        assert_eq!(source.fetched().len(), 6);
+    }
+
+    #[test]
+    fn test_shared_link_has_one_node() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let mut waffle = titled_page("/wiki/Waffle", "Waffle");
+        waffle.body = Some("<a href=\"/wiki/Belgium\" title=\"Belgium\">Belgium</a>".to_string());
+
+        let mut stroopwafel = titled_page("/wiki/Stroopwafel", "Stroopwafel");
+        stroopwafel.body = Some("<a href=\"/wiki/belgium\" title=\"Belgium\">Belgian</a>".to_string());
+
+        let waffle = graph.add_page(waffle);
+        let stroopwafel = graph.add_page(stroopwafel);
+
+        graph.expand_page(waffle).unwrap();
+        graph.expand_page(stroopwafel).unwrap();
+
+        let belgium = graph
+            .node_for_url(&WikipediaUrl::from_path("/wiki/Belgium").unwrap())
+            .unwrap();
+
+        assert_eq!(graph.graph.node_count(), 3);
+        assert_eq!(graph.graph.neighbors_directed(belgium, Direction::Incoming).count(), 2);
+        assert_eq!(graph.add_page(titled_page("/wiki/Belgium", "Belgium")), belgium);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut graph: WikipediaGraph<u32, petgraph_lib::Directed, usize> = WikipediaGraph::new();
+
+        let waffle = graph.add_page(titled_page("/wiki/Waffle", "Waffle"));
+        let batter = graph.add_page(titled_page("/wiki/Batter_(cooking)", "Batter (cooking)"));
+        let belgium = graph.add_page(titled_page("/wiki/Belgium", "Belgium"));
+
+        graph.graph.add_edge(waffle, batter, EdgeKind::Link);
+        graph.graph.add_edge(waffle, belgium, EdgeKind::SeeAlso);
+        graph.set_node_data(belgium, 7).unwrap();
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let deserialized: WikipediaGraph<u32, petgraph_lib::Directed, usize> = serde_json::from_str(&json).unwrap();
+
+        let belgium = deserialized
+            .node_for_url(&WikipediaUrl::from_path("/wiki/Belgium").unwrap())
+            .unwrap();
+
+        assert_eq!(deserialized.graph.node_count(), 3);
+        assert_eq!(deserialized.get_node_data(belgium), Some(&7));
+        assert_eq!(deserialized.to_dot(), graph.to_dot());
+        assert_eq!(
+            deserialized
+                .graph
+                .edge_references()
+                .map(|edge| *edge.weight())
+                .collect::<Vec<_>>(),
+            vec![EdgeKind::Link, EdgeKind::SeeAlso]
+        );
+
+        assert!(serde_json::from_str::<WikipediaGraph>(r#"{"nodes":[],"edges":[[0,1,"Link"]]}"#).is_err());
+    }
+
+    #[test]
+    fn test_crawl_from() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let mut belgium = titled_page("/wiki/Belgium", "Belgium");
+        belgium.body = Some(
+            concat!(
+                "<a href=\"/wiki/Waffle\" title=\"Waffle\">waffles</a>",
+                "<a href=\"/wiki/Europe\" title=\"Europe\">Europe</a>",
+            )
+            .to_string(),
+        );
+
+        let mut budget_graph: WikipediaGraph = WikipediaGraph::new();
+        budget_graph.crawl_from(belgium.clone(), 1, 2).unwrap();
+
+        assert_eq!(budget_graph.graph.node_count(), 2);
+
+        let belgium = graph.add_page(belgium);
+
+        let mut waffle = titled_page("/wiki/Waffle", "Waffle");
+        waffle.body = Some("<a href=\"/wiki/Belgium\" title=\"Belgium\">Belgium</a>".to_string());
+
+        let start = graph.crawl_from(waffle, 2, 10).unwrap();
+
+        assert_eq!(graph.graph.node_count(), 3);
+        assert_eq!(graph.graph.edge_count(), 3);
+        assert!(graph.graph.contains_edge(start, belgium));
+        assert!(graph.graph.contains_edge(belgium, start));
+
+        graph.graph[start].body = None;
+
+        assert_eq!(graph.crawl_from(titled_page("/wiki/Waffle", "Waffle"), 0, 10).unwrap(), start);
+        assert_eq!(graph.graph.node_count(), 3);
+    }
+
+    #[test]
+    fn test_expand_skips_self_loops() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let mut waffle = titled_page("/wiki/Waffle", "Waffle");
+        waffle.body = Some(
+            concat!(
+                "<a href=\"/wiki/Waffle\" title=\"Waffle\">Waffle</a>",
+                "<a href=\"/wiki/waffle#History\" title=\"Waffle\">history</a>",
+                "<a href=\"/wiki/Belgium\" title=\"Belgium\">Belgium</a>",
+            )
+            .to_string(),
+        );
+
+        let waffle = graph.add_page(waffle);
+
+        graph.expand_page(waffle).unwrap();
+
+        assert_eq!(graph.graph.node_count(), 2);
+        assert_eq!(graph.graph.edge_count(), 1);
+        assert!(graph.graph.find_edge(waffle, waffle).is_none());
+    }
+
+    #[test]
+    fn test_max_total_nodes() {
+        let mut belgium = titled_page("/wiki/Belgium", "Belgium");
+        belgium.body = Some(
+            concat!(
+                "<a href=\"/wiki/Waffle\" title=\"Waffle\">waffles</a>",
+                "<a href=\"/wiki/Europe\" title=\"Europe\">Europe</a>",
+                "<a href=\"/wiki/Brussels\" title=\"Brussels\">Brussels</a>",
+            )
+            .to_string(),
+        );
+
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+        graph.set_max_total_nodes(Some(3));
+
+        let europe = graph.add_page(titled_page("/wiki/Europe", "Europe"));
+        let belgium = graph.add_page(belgium);
+
+        assert!(matches!(graph.expand_page(belgium), Err(CrawlError::NodeBudgetReachedError(3))));
+        assert_eq!(graph.graph.node_count(), 3);
+        assert_eq!(graph.graph.edge_count(), 2);
+        assert!(graph.graph.contains_edge(belgium, europe));
+
+        assert!(matches!(
+            graph.crawl_from(titled_page("/wiki/Pancake", "Pancake"), 1, 10),
+            Err(CrawlError::NodeBudgetReachedError(3))
+        ));
+        assert_eq!(graph.graph.node_count(), 3);
+
+        graph.set_max_total_nodes(None);
+        graph.expand_page(belgium).unwrap();
+
+        assert_eq!(graph.graph.node_count(), 4);
+    }
+
+    #[test]
+    fn test_expand_skips_list_articles() {
+        let mut graph: WikipediaGraph = WikipediaGraph::with_options(CrawlOptions::new().skip_list_articles(true));
+
+        let mut waffle = titled_page("/wiki/Waffle", "Waffle");
+        waffle.body = Some(
+            concat!(
+                "<a href=\"/wiki/Batter\" title=\"Batter\">batter</a>",
+                "<a href=\"/wiki/List_of_breakfast_foods\" title=\"List of breakfast foods\">breakfast foods</a>",
+            )
+            .to_string(),
+        );
+
+        let waffle = graph.graph.add_node(waffle);
+
+        graph.expand_page(waffle).unwrap();
+
+        assert_eq!(graph.graph.node_count(), 2);
+        let batter = graph.graph.neighbors(waffle).next().unwrap();
+
+        assert_eq!(graph.graph[batter].get_url().path(), "/wiki/Batter");
+    }
+
+    #[test]
+    fn test_no_links_policy() {
+        let mut page = titled_page("/wiki/Waffle", "Waffle");
+        page.body = Some(format!("<p>{}</p>", "A waffle is a dish made from leavened batter. ".repeat(64)));
+
+        let mut graph: WikipediaGraph = WikipediaGraph::with_options(CrawlOptions::new().on_no_links(NoLinksPolicy::Error));
+        let waffle = graph.graph.add_node(page.clone());
+
+        assert!(matches!(graph.expand_page(waffle), Err(CrawlError::NoLinksFoundError(_))));
+
+        graph.set_options(CrawlOptions::new().on_no_links(NoLinksPolicy::Ignore));
+
+        assert!(graph.expand_page(waffle).is_ok());
+        assert_eq!(graph.graph.node_count(), 1);
+
+        let skipped = Arc::new(Mutex::new(Vec::new()));
+        let record = skipped.clone();
+        graph.set_options(CrawlOptions::new().on_no_links(NoLinksPolicy::warn(move |page| {
+            record.lock().unwrap().push(page.get_url().to_string());
+        })));
+
+        assert!(graph.expand_page(waffle).is_ok());
+        assert_eq!(*skipped.lock().unwrap(), ["https://en.wikipedia.org/wiki/Waffle"]);
+        assert!(matches!(CrawlOptions::new().on_no_links, NoLinksPolicy::Ignore));
+
+        page.body = Some("<p>Stub</p>".to_string());
+        graph.graph[waffle] = page;
+        graph.set_options(CrawlOptions::new().on_no_links(NoLinksPolicy::Error));
+
+        assert!(graph.expand_page(waffle).is_ok());
+    }
+
+    #[test]
+    fn test_subgraph_by_titles() {
+        let mut graph: WikipediaGraph<u32, petgraph_lib::Directed, u8> = WikipediaGraph::new();
+
+        let waffle = graph.graph.add_node(titled_page("/wiki/Waffle", "Waffle"));
+        let belgium = graph.graph.add_node(titled_page("/wiki/Belgium", "Belgium"));
+        let europe = graph.graph.add_node(Page::new(WikipediaUrl::from_path("/wiki/Western_Europe").unwrap()));
+
+        graph.graph.add_edge(waffle, belgium, EdgeKind::Link);
+        graph.graph.add_edge(belgium, europe, EdgeKind::Link);
+        graph.graph.add_edge(waffle, europe, EdgeKind::SeeAlso);
+        graph.set_node_data(europe, 7).unwrap();
+
+        let subgraph = graph.subgraph_by_titles(&["Waffle", "Western Europe"]);
+
+        assert_eq!(subgraph.graph.node_count(), 2);
+        assert_eq!(subgraph.graph.edge_count(), 1);
+
+        let edge = (&subgraph.graph).edge_references().next().unwrap();
+
+        assert_eq!(*edge.weight(), EdgeKind::SeeAlso);
+        assert_eq!(subgraph.graph[edge.source()].title.as_deref(), Some("Waffle"));
+        assert_eq!(subgraph.get_node_data(edge.target()), Some(&7));
+    }
+
+    #[test]
+    fn test_all_paths() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let waffle = graph.graph.add_node(titled_page("/wiki/Waffle", "Waffle"));
+        let belgium = graph.graph.add_node(titled_page("/wiki/Belgium", "Belgium"));
+        let france = graph.graph.add_node(titled_page("/wiki/France", "France"));
+        let europe = graph.graph.add_node(titled_page("/wiki/Europe", "Europe"));
+
+        graph.graph.add_edge(waffle, belgium, EdgeKind::Link);
+        graph.graph.add_edge(waffle, belgium, EdgeKind::SeeAlso);
+        graph.graph.add_edge(belgium, europe, EdgeKind::Link);
+        graph.graph.add_edge(waffle, france, EdgeKind::Link);
+        graph.graph.add_edge(france, belgium, EdgeKind::Link);
+        graph.graph.add_edge(waffle, europe, EdgeKind::Link);
+
+        let mut paths = graph.all_paths(waffle, europe, 2);
+        paths.sort_by_key(Vec::len);
+
+        assert_eq!(paths, vec![vec![waffle, europe], vec![waffle, belgium, europe]]);
+        assert_eq!(graph.all_paths(waffle, europe, 3).len(), 3);
+        assert!(graph.all_paths(europe, waffle, 3).is_empty());
+    }
+
+    #[test]
+    fn test_loaded_titles() {
+        let source = MockSource::new("<title>Fetched - Wikipedia</title>");
+        let page = |path: &str| Page::with_source(WikipediaUrl::from_path(path).unwrap(), source.clone());
+
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let mut waffle = page("/wiki/Waffle");
+        waffle.title = Some("Waffle".to_string());
+
+        let mut belgium = page("/wiki/Belgium");
+        belgium.body = Some("<title>Belgium - Wikipedia</title>".to_string());
+
+        let waffle = graph.add_page(waffle);
+        let belgium = graph.add_page(belgium);
+        let europe = graph.add_page(page("/wiki/Europe"));
+
+        graph.graph.add_edge(waffle, belgium, EdgeKind::Link);
+
+        assert_eq!(
+            graph.loaded_titles(),
+            vec![
+                (waffle, Some("Waffle".to_string())),
+                (belgium, Some("Belgium".to_string())),
+                (europe, None),
+            ]
+        );
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 1);
+        assert!(source.fetched().is_empty());
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let waffle = graph.add_page(titled_page("/wiki/Waffle", "Waffle"));
+        let belgium = graph.add_page(titled_page("/wiki/Belgium", "Belgium"));
+        let france = graph.add_page(titled_page("/wiki/France", "France"));
+        let europe = graph.add_page(titled_page("/wiki/Europe", "Europe"));
+        let asia = graph.add_page(titled_page("/wiki/Asia", "Asia"));
+
+        graph.graph.add_edge(waffle, france, EdgeKind::Link);
+        graph.graph.add_edge(france, belgium, EdgeKind::Link);
+        graph.graph.add_edge(belgium, europe, EdgeKind::Link);
+        graph.graph.add_edge(waffle, belgium, EdgeKind::SeeAlso);
+        graph.graph.add_edge(europe, waffle, EdgeKind::Link);
+
+        let path = graph.shortest_path(waffle, europe).unwrap();
+
+        assert_eq!(path, vec![waffle, belgium, europe]);
+        assert_eq!(path.len() - 1, 2);
+        assert_eq!(graph.path_titles(&path), vec!["Waffle", "Belgium", "Europe"]);
+
+        assert_eq!(graph.shortest_path(waffle, waffle), Some(vec![waffle]));
+        assert_eq!(graph.shortest_path(waffle, asia), None);
+    }
+
+    #[test]
+    fn test_top_pagerank() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        assert!(graph.top_pagerank(3).is_empty());
+
+        let waffle = graph.graph.add_node(titled_page("/wiki/Waffle", "Waffle"));
+        let belgium = graph.graph.add_node(titled_page("/wiki/Belgium", "Belgium"));
+        let europe = graph.graph.add_node(titled_page("/wiki/Europe", "Europe"));
+        let syrup = graph.graph.add_node(titled_page("/wiki/Syrup", "Syrup"));
+
+        graph.graph.add_edge(waffle, europe, EdgeKind::Link);
+        graph.graph.add_edge(belgium, europe, EdgeKind::Link);
+        graph.graph.add_edge(syrup, europe, EdgeKind::Link);
+        graph.graph.add_edge(europe, belgium, EdgeKind::Link);
+
+        let top = graph.top_pagerank(2);
+
+        assert_eq!(top.len(), 2);
+        assert_eq!((top[0].0, top[0].2.as_str()), (europe, "Europe"));
+        assert_eq!(top[1].0, belgium);
+        assert!(top[0].1 > top[1].1);
+        assert!((graph.pagerank().values().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_most_linked() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let waffle = graph.add_page(titled_page("/wiki/Waffle", "Waffle"));
+        let belgium = graph.add_page(titled_page("/wiki/Belgium", "Belgium"));
+        let europe = graph.add_page(titled_page("/wiki/Europe", "Europe"));
+        let france = graph.add_page(titled_page("/wiki/France", "France"));
+
+        graph.graph.add_edge(waffle, belgium, EdgeKind::Link);
+        graph.graph.add_edge(waffle, europe, EdgeKind::Link);
+        graph.graph.add_edge(belgium, europe, EdgeKind::Link);
+        graph.graph.add_edge(france, europe, EdgeKind::Link);
+        graph.graph.add_edge(france, belgium, EdgeKind::SeeAlso);
+
+        assert_eq!(graph.out_degree(waffle), 2);
+        assert_eq!(graph.in_degree(waffle), 0);
+        assert_eq!(graph.in_degree(europe), 3);
+        assert_eq!(graph.out_degree(europe), 0);
+
+        assert_eq!(
+            graph.most_linked(3),
+            vec![
+                (europe, 3, "Europe".to_string()),
+                (belgium, 2, "Belgium".to_string()),
+                (waffle, 0, "Waffle".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_degree_assortativity() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        assert!(graph.degree_assortativity().is_nan());
+
+        let waffle = graph.graph.add_node(titled_page("/wiki/Waffle", "Waffle"));
+        let belgium = graph.graph.add_node(titled_page("/wiki/Belgium", "Belgium"));
+        let europe = graph.graph.add_node(titled_page("/wiki/Europe", "Europe"));
+
+        graph.graph.add_edge(waffle, belgium, EdgeKind::Link);
+        graph.graph.add_edge(waffle, europe, EdgeKind::Link);
+        graph.graph.add_edge(belgium, europe, EdgeKind::Link);
+
+        assert!((graph.degree_assortativity() + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_clustering_coefficient() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        assert!(graph.average_clustering().is_nan());
+
+        let waffle = graph.graph.add_node(titled_page("/wiki/Waffle", "Waffle"));
+        let belgium = graph.graph.add_node(titled_page("/wiki/Belgium", "Belgium"));
+        let europe = graph.graph.add_node(titled_page("/wiki/Europe", "Europe"));
+        let batter = graph.graph.add_node(titled_page("/wiki/Batter", "Batter"));
+
+        graph.graph.add_edge(waffle, belgium, EdgeKind::Link);
+        graph.graph.add_edge(waffle, europe, EdgeKind::Link);
+        graph.graph.add_edge(europe, belgium, EdgeKind::Link);
+        graph.graph.add_edge(belgium, europe, EdgeKind::Link);
+        graph.graph.add_edge(batter, waffle, EdgeKind::Link);
+        graph.graph.add_edge(waffle, waffle, EdgeKind::Link);
+
+        assert!((graph.clustering_coefficient(waffle) - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(graph.clustering_coefficient(belgium), 1.0);
+        assert_eq!(graph.clustering_coefficient(batter), 0.0);
+        assert!((graph.average_clustering() - (1.0 / 3.0 + 2.0) / 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        assert_eq!(
+            two_node_graph().to_dot(),
+            "digraph {\n    0 [label=\"Waffle\"];\n    1 [label=\"Belgium, \\\"the\\\" country\"];\n    0 -> 1;\n}\n"
+        )
+    }
+
+    #[test]
+    fn test_export_disambiguators() {
+        let mut graph: WikipediaGraph = WikipediaGraph::new();
+
+        let planet = graph.graph.add_node(Page::new(WikipediaUrl::from_path("/wiki/Mercury_(planet)").unwrap()));
+        let element = graph.graph.add_node(titled_page("/wiki/Mercury_(element)", "Mercury (element)"));
+        let usa = graph.graph.add_node(titled_page("/wiki/USA", "United States"));
+        graph.graph.add_edge(planet, element, EdgeKind::Link);
+        graph.graph.add_edge(planet, usa, EdgeKind::Link);
+
+        let options = ExportOptions::new().strip_disambiguators(true);
+
+        assert!(graph.to_dot().contains(
+            "    0 [label=\"Mercury (planet)\"];\n    1 [label=\"Mercury (element)\"];\n    2 [label=\"United States\"];\n"
+        ));
+        assert!(graph.to_graphml().contains("<data key=\"title\">Mercury (element)</data>"));
+        assert!(graph
+            .to_dot_with_options(&options)
+            .contains("    0 [label=\"Mercury\"];\n    1 [label=\"Mercury\"];\n"));
+    }
+
+    #[test]
+    fn test_cocitation_edges_in_exports() {
+        let mut graph = two_node_graph();
+
+        let syrup = graph.graph.add_node(titled_page("/wiki/Syrup", "Syrup"));
+        let waffle = graph.graph.node_indices().next().unwrap();
+        graph.graph.add_edge(waffle, syrup, EdgeKind::CoCitation(3));
+
+        assert!(graph.to_dot().contains("    0 -> 2 [weight=3];\n"));
+        assert!(graph.to_graphml().contains("<edge source=\"n0\" target=\"n2\"><data key=\"weight\">3</data></edge>"));
+    }
+
+    #[test]
+    fn test_to_graphml() {
+        let graphml = two_node_graph().to_graphml();
+
+        assert!(graphml.contains("<data key=\"title\">Belgium, &quot;the&quot; country</data>"));
+        assert!(graphml.contains("<edge source=\"n0\" target=\"n1\"/>"));
+    }
+
+    #[test]
+    fn test_from_edge_list_csv() {
+        let csv = "source,target\nwaffle,Belgium\n\n\"Belgium, \"\"the\"\" country\",Europe\nBelgium,Europe\nwaffle,Europe\n";
+
+        let graph: WikipediaGraph = WikipediaGraph::from_edge_list_csv(csv.as_bytes()).unwrap();
+
+        assert_eq!(graph.graph.node_count(), 4);
+        assert_eq!(graph.graph.edge_count(), 4);
+
+        let waffle = graph
+            .graph
+            .node_indices()
+            .find(|index| graph.graph[*index].title.as_deref() == Some("waffle"))
+            .unwrap();
+
+        assert_eq!(graph.graph[waffle].get_url().path(), "/wiki/Waffle");
+        assert_eq!(graph.graph.neighbors(waffle).count(), 2);
+
+        let csv = "waffle,Belgian_waffle\nWaffle,Belgian waffle\nBelgian waffle,Belgium\n";
+        let variants: WikipediaGraph = WikipediaGraph::from_edge_list_csv(csv.as_bytes()).unwrap();
+
+        let waffle = variants.node_for_url(&WikipediaUrl::from_title("Waffle").unwrap()).unwrap();
+
+        assert_eq!(variants.graph.node_count(), 3);
+        assert_eq!(variants.graph.edge_count(), 2);
+        assert_eq!(variants.graph[waffle].title.as_deref(), Some("waffle"));
+
+        let csv = two_node_graph().to_edge_csv();
+        let round_trip: WikipediaGraph = WikipediaGraph::from_edge_list_csv(csv.as_bytes()).unwrap();
+        assert_eq!(round_trip.to_edge_csv(), csv);
+
+        assert!(matches!(
+            WikipediaGraph::<u32>::from_edge_list_csv("Waffle,Belgium,Europe".as_bytes()),
+            Err(super::EdgeListError::MalformedRowError(1))
+        ));
+    }
+
+    #[test]
+    fn test_from_edge_list_csv_lines() {
+        let csv = "source,target\r\n\"Waffle\n(dish)\",Belgium\r\n\r\n,Belgium\r\n";
+
+        assert!(matches!(
+            WikipediaGraph::<u32>::from_edge_list_csv(csv.as_bytes()),
+            Err(super::EdgeListError::EmptyTitleError(5))
+        ));
+        assert!(matches!(
+            WikipediaGraph::<u32>::from_edge_list_csv("Waffle,Belgium\nBelgium, \n".as_bytes()),
+            Err(super::EdgeListError::EmptyTitleError(2))
+        ));
+
+        let csv = "\"Waffle, \"\"Belgian\"\"\nstyle\",Belgium\nBelgium,Europe,Earth\n";
+
+        assert!(matches!(
+            WikipediaGraph::<u32>::from_edge_list_csv(csv.as_bytes()),
+            Err(super::EdgeListError::MalformedRowError(3))
+        ));
+    }
+
+    #[test]
+    fn test_write_edge_csv_matches_string() {
+        let graph = two_node_graph();
+        let mut buffer = Vec::new();
+
+        graph.write_edge_csv(&mut buffer).unwrap();
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), graph.to_edge_csv());
+        assert_eq!(
+            graph.to_edge_csv(),
+            "source,target\nWaffle,\"Belgium, \"\"the\"\" country\"\n"
+        )
+    }
+
+    #[test]
+    fn test_to_node_csv() {
+        assert_eq!(
+            two_node_graph().to_node_csv(),
+            concat!(
+                "node_id,title,url\n",
+                "0,Waffle,https://en.wikipedia.org/wiki/Waffle\n",
+                "1,\"Belgium, \"\"the\"\" country\",https://en.wikipedia.org/wiki/Belgium\n",
+            )
+        );
+    }
+}